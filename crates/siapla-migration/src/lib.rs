@@ -2,6 +2,29 @@ pub use sea_orm_migration::prelude::*;
 
 mod m20250322_create_tables;
 mod m20251014_add_allocation_booking;
+mod m20251020_add_task_split_policy;
+mod m20251021_add_availability_times;
+mod m20251022_add_availability_patterns;
+mod m20251023_add_availability_exception;
+mod m20251024_add_partial_days;
+mod m20251025_add_school_holidays;
+mod m20251026_add_holiday_provider;
+mod m20251027_add_holiday_calendars;
+mod m20251028_add_holiday_refresh;
+mod m20251029_add_subdivision_catalogue;
+mod m20251030_add_blocked_time;
+mod m20251031_add_resource_focus;
+mod m20251101_add_teams;
+mod m20251102_add_placeholder_resources;
+mod m20251103_add_ramp_up;
+mod m20251104_add_projects;
+mod m20251105_add_project_priority;
+mod m20251106_add_users;
+mod m20251107_add_user_roles;
+mod m20251108_add_audit_log;
+mod m20251109_add_change_sets;
+mod m20251110_add_versions;
+mod m20251111_add_calculation_runs;
 pub struct Migrator;
 
 #[async_trait::async_trait]
@@ -10,6 +33,29 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m20250322_create_tables::Migration),
             Box::new(m20251014_add_allocation_booking::Migration),
+            Box::new(m20251020_add_task_split_policy::Migration),
+            Box::new(m20251021_add_availability_times::Migration),
+            Box::new(m20251022_add_availability_patterns::Migration),
+            Box::new(m20251023_add_availability_exception::Migration),
+            Box::new(m20251024_add_partial_days::Migration),
+            Box::new(m20251025_add_school_holidays::Migration),
+            Box::new(m20251026_add_holiday_provider::Migration),
+            Box::new(m20251027_add_holiday_calendars::Migration),
+            Box::new(m20251028_add_holiday_refresh::Migration),
+            Box::new(m20251029_add_subdivision_catalogue::Migration),
+            Box::new(m20251030_add_blocked_time::Migration),
+            Box::new(m20251031_add_resource_focus::Migration),
+            Box::new(m20251101_add_teams::Migration),
+            Box::new(m20251102_add_placeholder_resources::Migration),
+            Box::new(m20251103_add_ramp_up::Migration),
+            Box::new(m20251104_add_projects::Migration),
+            Box::new(m20251105_add_project_priority::Migration),
+            Box::new(m20251106_add_users::Migration),
+            Box::new(m20251107_add_user_roles::Migration),
+            Box::new(m20251108_add_audit_log::Migration),
+            Box::new(m20251109_add_change_sets::Migration),
+            Box::new(m20251110_add_versions::Migration),
+            Box::new(m20251111_add_calculation_runs::Migration),
         ]
     }
 }
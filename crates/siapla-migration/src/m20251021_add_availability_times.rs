@@ -0,0 +1,189 @@
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{ConnectionTrait, Statement, prelude::ChronoTime},
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Convert a duration (hours) to a block centered around noon, as the planner used to do.
+/// An end time of 00:00 denotes the end of the day. Zero durations have no block.
+fn noon_centered_block(hours: f64) -> Option<(ChronoTime, ChronoTime)> {
+    if hours <= 0.0 {
+        return None;
+    }
+    let half = ((hours * 3600.0 / 2.0).round() as u32).min(12 * 3600);
+    let start = ChronoTime::from_num_seconds_from_midnight_opt(12 * 3600 - half, 0)
+        .expect("Must be a valid time");
+    let end = ChronoTime::from_num_seconds_from_midnight_opt((12 * 3600 + half) % (24 * 3600), 0)
+        .expect("Must be a valid time");
+    Some((start, end))
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Add start and end (local time of day) to Availability table
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Availability::Table)
+                    .add_column(
+                        ColumnDef::new(Availability::Start).time().not_null().default("00:00:00"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Availability::Table)
+                    .add_column(
+                        ColumnDef::new(Availability::End).time().not_null().default("00:00:00"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing rows only have a duration: convert them to noon-centered blocks, rows without
+        // working time are removed
+        let db = manager.get_connection();
+        let rows = db
+            .query_all(Statement::from_string(
+                manager.get_database_backend(),
+                "SELECT id, CAST(duration AS REAL) AS hours FROM availability",
+            ))
+            .await?;
+        let mut empty: Vec<i32> = vec![];
+        for row in rows {
+            let id: i32 = row.try_get("", "id")?;
+            let hours: f32 = row.try_get("", "hours")?;
+            let Some((start, end)) = noon_centered_block(hours as f64) else {
+                empty.push(id);
+                continue;
+            };
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(Availability::Table)
+                        .values([
+                            (Availability::Start, start.into()),
+                            (Availability::End, end.into()),
+                        ])
+                        .and_where(Expr::col(Availability::Id).eq(id))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        if !empty.is_empty() {
+            manager
+                .exec_stmt(
+                    Query::delete()
+                        .from_table(Availability::Table)
+                        .and_where(Expr::col(Availability::Id).is_in(empty))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Availability::Table)
+                    .drop_column(Availability::Duration)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Availability::Table)
+                    .add_column(
+                        ColumnDef::new(Availability::Duration).decimal().not_null().default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Multiple blocks per weekday are merged into a single duration
+        let db = manager.get_connection();
+        let rows = db
+            .query_all(Statement::from_string(
+                manager.get_database_backend(),
+                "SELECT id, resource_id, weekday, start, \"end\" FROM availability ORDER BY id",
+            ))
+            .await?;
+        let mut kept: Vec<((i32, String), i32, f64)> = vec![];
+        let mut removed: Vec<i32> = vec![];
+        for row in rows {
+            let id: i32 = row.try_get("", "id")?;
+            let key = (row.try_get::<i32>("", "resource_id")?, row.try_get("", "weekday")?);
+            let start: ChronoTime = row.try_get("", "start")?;
+            let end: ChronoTime = row.try_get("", "end")?;
+            let mut secs = (end - start).num_seconds();
+            if secs <= 0 {
+                secs += 24 * 3600;
+            }
+            let hours = secs as f64 / 3600.0;
+            if let Some(entry) = kept.iter_mut().find(|(k, _, _)| *k == key) {
+                entry.2 += hours;
+                removed.push(id);
+            } else {
+                kept.push((key, id, hours));
+            }
+        }
+        for (_, id, hours) in kept {
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(Availability::Table)
+                        .value(Availability::Duration, hours.min(24.0))
+                        .and_where(Expr::col(Availability::Id).eq(id))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        if !removed.is_empty() {
+            manager
+                .exec_stmt(
+                    Query::delete()
+                        .from_table(Availability::Table)
+                        .and_where(Expr::col(Availability::Id).is_in(removed))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Availability::Table)
+                    .drop_column(Availability::Start)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter().table(Availability::Table).drop_column(Availability::End).to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Availability {
+    Table,
+    Id,
+    Duration,
+    Start,
+    End,
+}
@@ -0,0 +1,92 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Date ranged availability patterns. Availability rows without a pattern form the default
+        // pattern of a resource.
+        manager
+            .create_table(
+                Table::create()
+                    .table(AvailabilityPattern::Table)
+                    .if_not_exists()
+                    .col(pk_auto(AvailabilityPattern::Id))
+                    .col(integer(AvailabilityPattern::ResourceId))
+                    .col(date_null(AvailabilityPattern::ValidFrom))
+                    .col(date_null(AvailabilityPattern::ValidUntil))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_AvailabilityPattern_Resource")
+                            .from(AvailabilityPattern::Table, AvailabilityPattern::ResourceId)
+                            .to(Resource::Table, Resource::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // SQLite cannot add foreign keys to existing tables, rows of removed patterns are deleted
+        // by the application.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Availability::Table)
+                    .add_column(ColumnDef::new(Availability::PatternId).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(Availability::Table)
+                    .and_where(Expr::col(Availability::PatternId).is_not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Availability::Table)
+                    .drop_column(Availability::PatternId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(AvailabilityPattern::Table).if_exists().to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum AvailabilityPattern {
+    Table,
+    Id,
+    ResourceId,
+    ValidFrom,
+    ValidUntil,
+}
+
+#[derive(DeriveIden)]
+enum Availability {
+    Table,
+    PatternId,
+}
+
+#[derive(DeriveIden)]
+enum Resource {
+    Table,
+    Id,
+}
@@ -0,0 +1,61 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Date specific working time of a resource. All rows of a date replace the working time
+        // of that day, a row without start / end means no working time.
+        manager
+            .create_table(
+                Table::create()
+                    .table(AvailabilityException::Table)
+                    .if_not_exists()
+                    .col(pk_auto(AvailabilityException::Id))
+                    .col(integer(AvailabilityException::ResourceId))
+                    .col(date(AvailabilityException::Date))
+                    .col(time_null(AvailabilityException::Start))
+                    .col(time_null(AvailabilityException::End))
+                    .col(string(AvailabilityException::Description).default(""))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_AvailabilityException_Resource")
+                            .from(AvailabilityException::Table, AvailabilityException::ResourceId)
+                            .to(Resource::Table, Resource::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AvailabilityException::Table).if_exists().to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum AvailabilityException {
+    Table,
+    Id,
+    ResourceId,
+    Date,
+    Start,
+    End,
+    Description,
+}
+
+#[derive(DeriveIden)]
+enum Resource {
+    Table,
+    Id,
+}
@@ -0,0 +1,66 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Add fraction (float, part of the working day that is off) to HolidayEntry table
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HolidayEntry::Table)
+                    .add_column(
+                        ColumnDef::new(HolidayEntry::Fraction).float().not_null().default(1.0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Add portion (string: Full, Morning, Afternoon) to Vacation table
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Vacation::Table)
+                    .add_column(
+                        ColumnDef::new(Vacation::Portion).string().not_null().default("Full"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HolidayEntry::Table)
+                    .drop_column(HolidayEntry::Fraction)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter().table(Vacation::Table).drop_column(Vacation::Portion).to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum HolidayEntry {
+    Table,
+    Fraction,
+}
+
+#[derive(DeriveIden)]
+enum Vacation {
+    Table,
+    Portion,
+}
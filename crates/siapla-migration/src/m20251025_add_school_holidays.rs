@@ -0,0 +1,139 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Add type (string: Public, School) to HolidayEntry table
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HolidayEntry::Table)
+                    .add_column(
+                        ColumnDef::new(HolidayEntry::Type).string().not_null().default("Public"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Add school_start and school_end (downloaded range of school holidays) to Holiday table
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Holiday::Table)
+                    .add_column(ColumnDef::new(Holiday::SchoolStart).date().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Holiday::Table)
+                    .add_column(ColumnDef::new(Holiday::SchoolEnd).date().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Add school_holiday_id (integer, references holiday) and school_holiday_mode (string:
+        // Reduced, Vacation) to Resource table. SQLite cannot add foreign keys to existing tables.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Resource::Table)
+                    .add_column(ColumnDef::new(Resource::SchoolHolidayId).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Resource::Table)
+                    .add_column(
+                        ColumnDef::new(Resource::SchoolHolidayMode)
+                            .string()
+                            .not_null()
+                            .default("Reduced"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(HolidayEntry::Table)
+                    .and_where(Expr::col(HolidayEntry::Type).ne("Public"))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HolidayEntry::Table)
+                    .drop_column(HolidayEntry::Type)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter().table(Holiday::Table).drop_column(Holiday::SchoolStart).to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter().table(Holiday::Table).drop_column(Holiday::SchoolEnd).to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Resource::Table)
+                    .drop_column(Resource::SchoolHolidayId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Resource::Table)
+                    .drop_column(Resource::SchoolHolidayMode)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum HolidayEntry {
+    Table,
+    Type,
+}
+
+#[derive(DeriveIden)]
+enum Holiday {
+    Table,
+    SchoolStart,
+    SchoolEnd,
+}
+
+#[derive(DeriveIden)]
+enum Resource {
+    Table,
+    SchoolHolidayId,
+    SchoolHolidayMode,
+}
@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Add provider (string: OpenHolidays, Ics, Manual) to Holiday table
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Holiday::Table)
+                    .add_column(
+                        ColumnDef::new(Holiday::Provider)
+                            .string()
+                            .not_null()
+                            .default("OpenHolidays"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter().table(Holiday::Table).drop_column(Holiday::Provider).to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Holiday {
+    Table,
+    Provider,
+}
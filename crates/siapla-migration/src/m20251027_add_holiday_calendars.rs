@@ -0,0 +1,98 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Additional holiday calendars of a resource, e.g. a company calendar on top of the
+        // regional public holidays referenced by resource.holiday_id
+        manager
+            .create_table(
+                Table::create()
+                    .table(ResourceHoliday::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ResourceHoliday::Id))
+                    .col(integer(ResourceHoliday::ResourceId))
+                    .col(integer(ResourceHoliday::HolidayId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_ResourceHoliday_Resource")
+                            .from(ResourceHoliday::Table, ResourceHoliday::ResourceId)
+                            .to(Resource::Table, Resource::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_ResourceHoliday_Holiday")
+                            .from(ResourceHoliday::Table, ResourceHoliday::HolidayId)
+                            .to(Holiday::Table, Holiday::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("IDX_ResourceHoliday_Unique")
+                    .table(ResourceHoliday::Table)
+                    .col(ResourceHoliday::ResourceId)
+                    .col(ResourceHoliday::HolidayId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Add global (bool, applies to all resources, e.g. company shutdown) to Holiday table
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Holiday::Table)
+                    .add_column(ColumnDef::new(Holiday::Global).boolean().not_null().default(false))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter().table(Holiday::Table).drop_column(Holiday::Global).to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(ResourceHoliday::Table).if_exists().to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum ResourceHoliday {
+    Table,
+    Id,
+    ResourceId,
+    HolidayId,
+}
+
+#[derive(DeriveIden)]
+enum Resource {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Holiday {
+    Table,
+    Id,
+    Global,
+}
@@ -0,0 +1,54 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Add language (ISO 639-1 code of the entry names) to Holiday table
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Holiday::Table)
+                    .add_column(ColumnDef::new(Holiday::Language).string().not_null().default("EN"))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Add refreshed_at (last time the cached entries were downloaded) to Holiday table
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Holiday::Table)
+                    .add_column(timestamp_null(Holiday::RefreshedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter().table(Holiday::Table).drop_column(Holiday::Language).to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter().table(Holiday::Table).drop_column(Holiday::RefreshedAt).to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Holiday {
+    Table,
+    Language,
+    RefreshedAt,
+}
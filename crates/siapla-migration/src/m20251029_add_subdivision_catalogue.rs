@@ -0,0 +1,54 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Offline catalogue of the subdivisions (regions) of all supported countries
+        manager
+            .create_table(
+                Table::create()
+                    .table(Subdivision::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Subdivision::Id))
+                    .col(string(Subdivision::CountryIsocode))
+                    .col(string(Subdivision::Code))
+                    .col(string_null(Subdivision::IsoCode))
+                    .col(string(Subdivision::Name))
+                    .col(string(Subdivision::ShortName))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("IDX_Subdivision_Country")
+                    .table(Subdivision::Table)
+                    .col(Subdivision::CountryIsocode)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Subdivision::Table).if_exists().to_owned()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Subdivision {
+    Table,
+    Id,
+    CountryIsocode,
+    Code,
+    IsoCode,
+    Name,
+    ShortName,
+}
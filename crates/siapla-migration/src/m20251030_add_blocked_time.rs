@@ -0,0 +1,66 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Recurring blocks of time a resource is not available for tasks (meetings, support duty)
+        manager
+            .create_table(
+                Table::create()
+                    .table(BlockedTime::Table)
+                    .if_not_exists()
+                    .col(pk_auto(BlockedTime::Id))
+                    .col(integer(BlockedTime::ResourceId))
+                    .col(string(BlockedTime::Description).default(""))
+                    .col(string(BlockedTime::Frequency).default("Weekly"))
+                    .col(integer(BlockedTime::Interval).default(1))
+                    .col(string_null(BlockedTime::Weekday))
+                    .col(time(BlockedTime::Start))
+                    .col(time(BlockedTime::End))
+                    .col(date_null(BlockedTime::ValidFrom))
+                    .col(date_null(BlockedTime::ValidUntil))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_BlockedTime_Resource")
+                            .from(BlockedTime::Table, BlockedTime::ResourceId)
+                            .to(Resource::Table, Resource::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(BlockedTime::Table).if_exists().to_owned()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum BlockedTime {
+    Table,
+    Id,
+    ResourceId,
+    Description,
+    Frequency,
+    Interval,
+    Weekday,
+    Start,
+    End,
+    ValidFrom,
+    ValidUntil,
+}
+
+#[derive(DeriveIden)]
+enum Resource {
+    Table,
+    Id,
+}
@@ -0,0 +1,58 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Share of the availability a resource spends on planned tasks, optionally date ranged
+        manager
+            .create_table(
+                Table::create()
+                    .table(ResourceFocus::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ResourceFocus::Id))
+                    .col(integer(ResourceFocus::ResourceId))
+                    .col(float(ResourceFocus::Factor).default(1.0))
+                    .col(date_null(ResourceFocus::ValidFrom))
+                    .col(date_null(ResourceFocus::ValidUntil))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_ResourceFocus_Resource")
+                            .from(ResourceFocus::Table, ResourceFocus::ResourceId)
+                            .to(Resource::Table, Resource::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ResourceFocus::Table).if_exists().to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum ResourceFocus {
+    Table,
+    Id,
+    ResourceId,
+    Factor,
+    ValidFrom,
+    ValidUntil,
+}
+
+#[derive(DeriveIden)]
+enum Resource {
+    Table,
+    Id,
+}
@@ -0,0 +1,112 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Team::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Team::Id))
+                    .col(string(Team::Name))
+                    .col(string(Team::Description).default(""))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Membership history: a resource belongs to a team from `joined` until `left`
+        manager
+            .create_table(
+                Table::create()
+                    .table(TeamMembership::Table)
+                    .if_not_exists()
+                    .col(pk_auto(TeamMembership::Id))
+                    .col(integer(TeamMembership::TeamId))
+                    .col(integer(TeamMembership::ResourceId))
+                    .col(timestamp(TeamMembership::Joined))
+                    .col(timestamp_null(TeamMembership::Left))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_TeamMembership_Team")
+                            .from(TeamMembership::Table, TeamMembership::TeamId)
+                            .to(Team::Table, Team::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_TeamMembership_Resource")
+                            .from(TeamMembership::Table, TeamMembership::ResourceId)
+                            .to(Resource::Table, Resource::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Constraints targeting any member of a team. SQLite cannot add foreign keys to existing
+        // tables.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ResourceConstraint::Table)
+                    .add_column(ColumnDef::new(ResourceConstraint::TeamId).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ResourceConstraint::Table)
+                    .drop_column(ResourceConstraint::TeamId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(TeamMembership::Table).if_exists().to_owned())
+            .await?;
+        manager.drop_table(Table::drop().table(Team::Table).if_exists().to_owned()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Team {
+    Table,
+    Id,
+    Name,
+    Description,
+}
+
+#[derive(DeriveIden)]
+enum TeamMembership {
+    Table,
+    Id,
+    TeamId,
+    ResourceId,
+    Joined,
+    Left,
+}
+
+#[derive(DeriveIden)]
+enum ResourceConstraint {
+    Table,
+    TeamId,
+}
+
+#[derive(DeriveIden)]
+enum Resource {
+    Table,
+    Id,
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Add placeholder (bool, planned hire instead of a real person) and expected_start
+        // (timestamp, expected start of the hire) to Resource table
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Resource::Table)
+                    .add_column(
+                        ColumnDef::new(Resource::Placeholder).boolean().not_null().default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Resource::Table)
+                    .add_column(ColumnDef::new(Resource::ExpectedStart).timestamp().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Resource::Table)
+                    .drop_column(Resource::ExpectedStart)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter().table(Resource::Table).drop_column(Resource::Placeholder).to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Resource {
+    Table,
+    Placeholder,
+    ExpectedStart,
+}
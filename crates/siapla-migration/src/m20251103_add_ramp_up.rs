@@ -0,0 +1,54 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Onboarding profile: reduced speed until `days` after the resource was added
+        manager
+            .create_table(
+                Table::create()
+                    .table(RampUpStage::Table)
+                    .if_not_exists()
+                    .col(pk_auto(RampUpStage::Id))
+                    .col(integer(RampUpStage::ResourceId))
+                    .col(integer(RampUpStage::Days))
+                    .col(float(RampUpStage::Speed))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_RampUpStage_Resource")
+                            .from(RampUpStage::Table, RampUpStage::ResourceId)
+                            .to(Resource::Table, Resource::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(RampUpStage::Table).if_exists().to_owned()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum RampUpStage {
+    Table,
+    Id,
+    ResourceId,
+    Days,
+    Speed,
+}
+
+#[derive(DeriveIden)]
+enum Resource {
+    Table,
+    Id,
+}
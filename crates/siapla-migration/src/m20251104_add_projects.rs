@@ -0,0 +1,63 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Projects own tasks, resources are shared across projects. `modified_at` and
+        // `calculated_at` track whether the project's plan is up to date.
+        manager
+            .create_table(
+                Table::create()
+                    .table(Project::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Project::Id))
+                    .col(string(Project::Name))
+                    .col(string(Project::Description).default(""))
+                    .col(timestamp_null(Project::ModifiedAt))
+                    .col(timestamp_null(Project::CalculatedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Add project_id (integer, references project) to Task table. SQLite cannot add foreign
+        // keys to existing tables.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(ColumnDef::new(Task::ProjectId).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(Table::alter().table(Task::Table).drop_column(Task::ProjectId).to_owned())
+            .await?;
+        manager.drop_table(Table::drop().table(Project::Table).if_exists().to_owned()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Project {
+    Table,
+    Id,
+    Name,
+    Description,
+    ModifiedAt,
+    CalculatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Task {
+    Table,
+    ProjectId,
+}
@@ -0,0 +1,91 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Add priority (string, Low/Medium/High) to Project table
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Project::Table)
+                    .add_column(
+                        ColumnDef::new(Project::Priority).string().not_null().default("Medium"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Milestones delayed by another project: the milestone would be reached at
+        // `unaffected_date` without the competing project's tasks
+        manager
+            .create_table(
+                Table::create()
+                    .table(MilestoneContention::Table)
+                    .if_not_exists()
+                    .col(pk_auto(MilestoneContention::Id))
+                    .col(integer(MilestoneContention::MilestoneId))
+                    .col(integer(MilestoneContention::ProjectId))
+                    .col(timestamp_null(MilestoneContention::PlannedDate))
+                    .col(timestamp(MilestoneContention::UnaffectedDate))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_MilestoneContention_Task")
+                            .from(MilestoneContention::Table, MilestoneContention::MilestoneId)
+                            .to(Task::Table, Task::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_MilestoneContention_Project")
+                            .from(MilestoneContention::Table, MilestoneContention::ProjectId)
+                            .to(Project::Table, Project::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MilestoneContention::Table).if_exists().to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter().table(Project::Table).drop_column(Project::Priority).to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum MilestoneContention {
+    Table,
+    Id,
+    MilestoneId,
+    ProjectId,
+    PlannedDate,
+    UnaffectedDate,
+}
+
+#[derive(DeriveIden)]
+enum Project {
+    Table,
+    Id,
+    Priority,
+}
+
+#[derive(DeriveIden)]
+enum Task {
+    Table,
+    Id,
+}
@@ -0,0 +1,80 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // User accounts, the password is stored as argon2 hash
+        manager
+            .create_table(
+                Table::create()
+                    .table(User::Table)
+                    .if_not_exists()
+                    .col(pk_auto(User::Id))
+                    .col(string_uniq(User::Name))
+                    .col(string(User::PasswordHash))
+                    .col(timestamp(User::CreatedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Login sessions and API tokens, only the sha256 hash of a token is stored
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuthToken::Table)
+                    .if_not_exists()
+                    .col(pk_auto(AuthToken::Id))
+                    .col(integer(AuthToken::UserId))
+                    .col(string(AuthToken::Name).default(""))
+                    .col(string(AuthToken::Kind))
+                    .col(string_uniq(AuthToken::TokenHash))
+                    .col(timestamp(AuthToken::CreatedAt))
+                    .col(timestamp_null(AuthToken::ExpiresAt))
+                    .col(timestamp_null(AuthToken::LastUsedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_AuthToken_User")
+                            .from(AuthToken::Table, AuthToken::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(AuthToken::Table).if_exists().to_owned()).await?;
+        manager.drop_table(Table::drop().table(User::Table).if_exists().to_owned()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+    Name,
+    PasswordHash,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum AuthToken {
+    Table,
+    Id,
+    UserId,
+    Name,
+    Kind,
+    TokenHash,
+    CreatedAt,
+    ExpiresAt,
+    LastUsedAt,
+}
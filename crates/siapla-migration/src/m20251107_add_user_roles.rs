@@ -0,0 +1,51 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Add role (string, Viewer/Member/Planner/Admin) to User table, existing accounts keep
+        // full access
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(User::Role).string().not_null().default("Admin"))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Add resource_id (integer, references resource, the person using the account) to User
+        // table. SQLite cannot add foreign keys to existing tables.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(User::ResourceId).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(Table::alter().table(User::Table).drop_column(User::ResourceId).to_owned())
+            .await?;
+        manager
+            .alter_table(Table::alter().table(User::Table).drop_column(User::Role).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Role,
+    ResourceId,
+}
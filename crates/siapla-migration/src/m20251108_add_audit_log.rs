@@ -0,0 +1,76 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Changes of entities with their values before and after as JSON. The user name is kept
+        // for entries of deleted users.
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditLog::Table)
+                    .if_not_exists()
+                    .col(pk_auto(AuditLog::Id))
+                    .col(integer_null(AuditLog::UserId))
+                    .col(string_null(AuditLog::UserName))
+                    .col(string(AuditLog::EntityType))
+                    .col(integer(AuditLog::EntityId))
+                    .col(string(AuditLog::Action))
+                    .col(text_null(AuditLog::Before))
+                    .col(text_null(AuditLog::After))
+                    .col(timestamp(AuditLog::ChangedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_AuditLog_User")
+                            .from(AuditLog::Table, AuditLog::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("IDX_AuditLog_Entity")
+                    .table(AuditLog::Table)
+                    .col(AuditLog::EntityType)
+                    .col(AuditLog::EntityId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(AuditLog::Table).if_exists().to_owned()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum AuditLog {
+    Table,
+    Id,
+    UserId,
+    UserName,
+    EntityType,
+    EntityId,
+    Action,
+    Before,
+    After,
+    ChangedAt,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
@@ -0,0 +1,77 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Changes made by one request, undone and redone as a whole. State is
+        // Applied/Undone/Discarded.
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChangeSet::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ChangeSet::Id))
+                    .col(integer_null(ChangeSet::UserId))
+                    .col(string(ChangeSet::State).default("Applied"))
+                    .col(timestamp(ChangeSet::CreatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_ChangeSet_User")
+                            .from(ChangeSet::Table, ChangeSet::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Add change_set_id (integer, references change_set) to AuditLog table. SQLite cannot
+        // add foreign keys to existing tables.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuditLog::Table)
+                    .add_column(ColumnDef::new(AuditLog::ChangeSetId).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter().table(AuditLog::Table).drop_column(AuditLog::ChangeSetId).to_owned(),
+            )
+            .await?;
+        manager.drop_table(Table::drop().table(ChangeSet::Table).if_exists().to_owned()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum ChangeSet {
+    Table,
+    Id,
+    UserId,
+    State,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum AuditLog {
+    Table,
+    ChangeSetId,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
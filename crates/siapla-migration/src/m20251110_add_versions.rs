@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Add version (integer, incremented by every save) to Task table
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(ColumnDef::new(Task::Version).integer().not_null().default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Add version (integer, incremented by every save) to Resource table
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Resource::Table)
+                    .add_column(ColumnDef::new(Resource::Version).integer().not_null().default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter().table(Resource::Table).drop_column(Resource::Version).to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(Table::alter().table(Task::Table).drop_column(Task::Version).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Task {
+    Table,
+    Version,
+}
+
+#[derive(DeriveIden)]
+enum Resource {
+    Table,
+    Version,
+}
@@ -0,0 +1,103 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // One row per run of the scheduler. Trigger is Manual/Debounce, result is
+        // Finished/Cancelled/Failed. Settings of the genetic algorithm are stored as JSON.
+        manager
+            .create_table(
+                Table::create()
+                    .table(CalculationRun::Table)
+                    .if_not_exists()
+                    .col(pk_auto(CalculationRun::Id))
+                    .col(string(CalculationRun::Trigger))
+                    .col(string(CalculationRun::Result))
+                    .col(timestamp(CalculationRun::StartedAt))
+                    .col(timestamp(CalculationRun::FinishedAt))
+                    .col(text(CalculationRun::Settings))
+                    .col(big_integer(CalculationRun::Seed))
+                    .col(double_null(CalculationRun::Cost))
+                    .col(integer_null(CalculationRun::IssueCount))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Cost of every milestone in a finished run. The title is kept for deleted milestones.
+        manager
+            .create_table(
+                Table::create()
+                    .table(CalculationRunCost::Table)
+                    .if_not_exists()
+                    .col(pk_auto(CalculationRunCost::Id))
+                    .col(integer(CalculationRunCost::CalculationRunId))
+                    .col(integer_null(CalculationRunCost::MilestoneId))
+                    .col(string(CalculationRunCost::MilestoneTitle))
+                    .col(double(CalculationRunCost::Cost))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_CalculationRunCost_CalculationRun")
+                            .from(CalculationRunCost::Table, CalculationRunCost::CalculationRunId)
+                            .to(CalculationRun::Table, CalculationRun::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_CalculationRunCost_Task")
+                            .from(CalculationRunCost::Table, CalculationRunCost::MilestoneId)
+                            .to(Task::Table, Task::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CalculationRunCost::Table).if_exists().to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(CalculationRun::Table).if_exists().to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum CalculationRun {
+    Table,
+    Id,
+    Trigger,
+    Result,
+    StartedAt,
+    FinishedAt,
+    Settings,
+    Seed,
+    Cost,
+    IssueCount,
+}
+
+#[derive(DeriveIden)]
+enum CalculationRunCost {
+    Table,
+    Id,
+    CalculationRunId,
+    MilestoneId,
+    MilestoneTitle,
+    Cost,
+}
+
+#[derive(DeriveIden)]
+enum Task {
+    Table,
+    Id,
+}
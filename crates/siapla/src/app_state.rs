@@ -2,10 +2,13 @@ use std::sync::Arc;
 
 use tokio::sync::{broadcast, mpsc, watch};
 
+use crate::{entity::audit_log, scheduling::GAProgress};
+
 #[derive(Clone, Debug)]
 pub enum CalculationState {
     Modified,
-    Calculating,
+    /// Progress is none until the genetic algorithm is started
+    Calculating(Option<GAProgress>),
     Finished,
 }
 
@@ -13,30 +16,53 @@ pub enum CalculationState {
 pub struct AppState {
     /// broadcast channel for modification events (sender identity as String)
     pub modify_tx: broadcast::Sender<String>,
+    /// broadcast channel for committed changes of entities (their audit log entries)
+    pub change_tx: broadcast::Sender<audit_log::Model>,
     /// manual recalculation trigger (single receiver expected)
     pub manual_tx: mpsc::UnboundedSender<()>,
     /// watch channel for current calculation state
     pub state_tx: watch::Sender<CalculationState>,
+    /// set to cancel the running calculation, reset when a calculation starts
+    pub cancel_tx: watch::Sender<bool>,
 }
 
 impl AppState {
     /// Create a new AppState and return it together with the manual receiver.
     pub fn new() -> (Arc<Self>, mpsc::UnboundedReceiver<()>) {
         let (modify_tx, _modify_rx) = broadcast::channel(16);
+        let (change_tx, _change_rx) = broadcast::channel(256);
         let (manual_tx, manual_rx) = mpsc::unbounded_channel();
         let (state_tx, _state_rx) = watch::channel(CalculationState::Modified);
-        (Arc::new(Self { modify_tx, manual_tx, state_tx }), manual_rx)
+        let (cancel_tx, _cancel_rx) = watch::channel(false);
+        (Arc::new(Self { modify_tx, change_tx, manual_tx, state_tx, cancel_tx }), manual_rx)
     }
 
     pub fn notify_modified(&self, sender: impl Into<String>) {
         let _ = self.modify_tx.send(sender.into());
     }
 
+    /// Publish changes to subscribers, only call this after they were committed
+    pub fn publish_changes(&self, changes: impl IntoIterator<Item = audit_log::Model>) {
+        for change in changes {
+            let _ = self.change_tx.send(change);
+        }
+    }
+
     pub fn trigger_manual(&self) {
         let _ = self.manual_tx.send(());
     }
 
+    /// Cancel the running calculation, returns false if none is running
+    pub fn cancel_calculation(&self) -> bool {
+        if !matches!(*self.state_tx.borrow(), CalculationState::Calculating(_)) {
+            return false;
+        }
+        self.cancel_tx.send_replace(true);
+        true
+    }
+
+    /// Set the state, also without subscribers (`cancel_calculation` relies on it)
     pub fn set_state(&self, state: CalculationState) {
-        let _ = self.state_tx.send(state);
+        self.state_tx.send_replace(state);
     }
 }
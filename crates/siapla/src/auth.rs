@@ -0,0 +1,281 @@
+use std::sync::{Arc, OnceLock};
+
+use anyhow::anyhow;
+use argon2::{
+    Argon2, PasswordHash, PasswordHasher as _, PasswordVerifier as _,
+    password_hash::{SaltString, rand_core::OsRng},
+};
+use axum::{
+    Extension, Json,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use juniper::GraphQLEnum;
+use rand::Rng as _;
+use sea_orm::{ActiveValue, DatabaseTransaction, prelude::*};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use strum::{EnumString, IntoStaticStr};
+
+use crate::{
+    entity::{auth_token, user},
+    gql::context::Context,
+};
+
+/// Cookie holding the session token of the bundled frontend
+pub const SESSION_COOKIE: &str = "siapla_session";
+pub const SESSION_DAYS: i64 = 30;
+const MIN_PASSWORD_LENGTH: usize = 8;
+/// `last_used_at` of tokens is only updated after this many minutes: writing it on every request
+/// would make each request take the database's write lock
+const LAST_USED_RESOLUTION_MINUTES: i64 = 60;
+
+// set from the server's command line
+static AUTH_DISABLED: OnceLock<bool> = OnceLock::new();
+
+#[derive(GraphQLEnum, IntoStaticStr, EnumString, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum TokenKind {
+    /// Created by logging in, expires after `SESSION_DAYS`
+    Session,
+    /// Created by a user for scripts
+    Api,
+}
+
+impl From<TokenKind> for String {
+    fn from(value: TokenKind) -> Self {
+        let s: &'static str = value.into();
+        s.into()
+    }
+}
+
+/// Roles ordered by their permissions, each role may do everything the previous ones may do
+#[derive(
+    GraphQLEnum, IntoStaticStr, EnumString, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug,
+)]
+pub enum Role {
+    /// May read plans, but no private data of resources
+    Viewer,
+    /// May book time for the own resource
+    Member,
+    /// May edit tasks, resources, teams, projects and all bookings
+    Planner,
+    /// May change settings like holiday calendars and manage user accounts
+    Admin,
+}
+
+impl From<Role> for String {
+    fn from(value: Role) -> Self {
+        let s: &'static str = value.into();
+        s.into()
+    }
+}
+
+pub fn hash_password(password: &str) -> anyhow::Result<String> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(anyhow!("Passwords must have at least {} characters", MIN_PASSWORD_LENGTH));
+    }
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|err| anyhow!("Failed to hash password: {err}"))?;
+    Ok(hash.to_string())
+}
+
+pub fn verify_password(user: &user::Model, password: &str) -> bool {
+    PasswordHash::new(&user.password_hash)
+        .is_ok_and(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+}
+
+fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes()).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Create a new random token for `user`. The token itself is only returned here, the database
+/// only contains its hash.
+pub async fn create_token(
+    txn: &DatabaseTransaction,
+    user: &user::Model,
+    kind: TokenKind,
+    name: String,
+    expires_at: Option<DateTime<Utc>>,
+) -> anyhow::Result<(String, auth_token::Model)> {
+    let bytes: [u8; 32] = rand::rng().random();
+    let token: String =
+        format!("siapla_{}", bytes.iter().map(|b| format!("{b:02x}")).collect::<String>());
+    let am = auth_token::ActiveModel {
+        id: ActiveValue::NotSet,
+        user_id: ActiveValue::Set(user.id),
+        name: ActiveValue::Set(name),
+        kind: ActiveValue::Set(kind.into()),
+        token_hash: ActiveValue::Set(hash_token(&token)),
+        created_at: ActiveValue::Set(Utc::now()),
+        expires_at: ActiveValue::Set(expires_at),
+        last_used_at: ActiveValue::NotSet,
+    };
+    Ok((token, am.insert(txn).await?))
+}
+
+/// Find the user of a token that has not expired yet and record its use
+pub async fn authenticate(
+    txn: &DatabaseTransaction,
+    token: &str,
+) -> anyhow::Result<Option<user::Model>> {
+    let now = Utc::now();
+    let found = auth_token::Entity::find()
+        .filter(auth_token::Column::TokenHash.eq(hash_token(token)))
+        .find_also_related(user::Entity)
+        .one(txn)
+        .await?;
+    let Some((token, Some(user))) = found else {
+        return Ok(None);
+    };
+    if token.expires_at.is_some_and(|expires_at| expires_at <= now) {
+        return Ok(None);
+    }
+    let resolution = TimeDelta::minutes(LAST_USED_RESOLUTION_MINUTES);
+    if token.last_used_at.is_none_or(|last_used_at| now - last_used_at >= resolution) {
+        let mut am: auth_token::ActiveModel = token.into();
+        am.last_used_at = ActiveValue::Set(Some(now));
+        am.update(txn).await?;
+    }
+    Ok(Some(user))
+}
+
+/// Allow requests without login, they are handled with admin rights. Only meant for trusted
+/// networks, authentication is required unless this is called early in program startup.
+pub fn set_global_auth_disabled(disabled: bool) {
+    let _ = AUTH_DISABLED.set(disabled);
+}
+
+/// Whether requests without login are allowed, see `set_global_auth_disabled`
+pub fn auth_disabled() -> bool {
+    AUTH_DISABLED.get().copied().unwrap_or(false)
+}
+
+/// Whether any user account exists, nobody can log in to an installation without accounts. The
+/// first account is created with `create_admin`.
+pub async fn has_users<C: ConnectionTrait>(db: &C) -> anyhow::Result<bool> {
+    Ok(user::Entity::find().count(db).await? > 0)
+}
+
+/// Create an admin account unless a user with this name exists already, used to set up the first
+/// account of an installation. Returns whether the account was created.
+pub async fn create_admin(
+    txn: &DatabaseTransaction,
+    name: &str,
+    password: &str,
+) -> anyhow::Result<bool> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(anyhow!("User name must not be empty"));
+    }
+    if user::Entity::find().filter(user::Column::Name.eq(name)).one(txn).await?.is_some() {
+        return Ok(false);
+    }
+    let am = user::ActiveModel {
+        id: ActiveValue::NotSet,
+        name: ActiveValue::Set(name.to_owned()),
+        password_hash: ActiveValue::Set(hash_password(password)?),
+        created_at: ActiveValue::Set(Utc::now()),
+        role: ActiveValue::Set(Role::Admin.into()),
+        resource_id: ActiveValue::Set(None),
+    };
+    am.insert(txn).await?;
+    Ok(true)
+}
+
+/// Token of a request, from the `Authorization: Bearer` header or the session cookie
+pub fn request_token(headers: &HeaderMap) -> Option<String> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if let Some(token) = bearer {
+        return Some(token.trim().to_owned());
+    }
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == SESSION_COOKIE)
+        .map(|(_, token)| token.to_owned())
+}
+
+#[derive(Deserialize)]
+pub struct LoginRequest {
+    name: String,
+    password: String,
+}
+
+#[derive(Serialize)]
+pub struct LoginResponse {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// `POST /auth/login`: create a session for the user and set the session cookie
+pub async fn login(
+    Extension(ctx): Extension<Arc<Context>>,
+    Json(request): Json<LoginRequest>,
+) -> Result<Response, StatusCode> {
+    let txn = ctx.txn().await.map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+    let user = user::Entity::find()
+        .filter(user::Column::Name.eq(request.name))
+        .one(txn)
+        .await
+        .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+    let Some(user) = user.filter(|user| verify_password(user, &request.password)) else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    let expires_at = Utc::now() + TimeDelta::days(SESSION_DAYS);
+    let (token, _) = create_token(txn, &user, TokenKind::Session, String::new(), Some(expires_at))
+        .await
+        .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+    let cookie = format!(
+        "{SESSION_COOKIE}={token}; Path=/; HttpOnly; SameSite=Strict; Max-Age={}",
+        SESSION_DAYS * 24 * 3600
+    );
+    Ok(([(header::SET_COOKIE, cookie)], Json(LoginResponse { token, expires_at })).into_response())
+}
+
+/// `POST /auth/logout`: end the session of the request and clear the session cookie
+pub async fn logout(
+    Extension(ctx): Extension<Arc<Context>>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    if let Some(token) = request_token(&headers) {
+        let txn = ctx.txn().await.map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+        auth_token::Entity::delete_many()
+            .filter(auth_token::Column::TokenHash.eq(hash_token(&token)))
+            .filter(auth_token::Column::Kind.eq(<&'static str>::from(TokenKind::Session)))
+            .exec(txn)
+            .await
+            .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+    }
+    let cookie = format!("{SESSION_COOKIE}=; Path=/; HttpOnly; SameSite=Strict; Max-Age=0");
+    Ok(([(header::SET_COOKIE, cookie)], StatusCode::NO_CONTENT).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_request_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(request_token(&headers), None);
+        headers.insert(header::COOKIE, HeaderValue::from_static("theme=dark; siapla_session=abc"));
+        assert_eq!(request_token(&headers).as_deref(), Some("abc"));
+        // bearer tokens take precedence over the session cookie
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer siapla_123 "));
+        assert_eq!(request_token(&headers).as_deref(), Some("siapla_123"));
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Basic dXNlcjpwdw=="));
+        assert_eq!(request_token(&headers).as_deref(), Some("abc"));
+        headers.insert(header::COOKIE, HeaderValue::from_static("siapla_session_old=x"));
+        assert_eq!(request_token(&headers), None);
+    }
+}
@@ -0,0 +1,72 @@
+use std::{fs, path::PathBuf};
+
+use clap::{Parser, Subcommand};
+use sea_orm::TransactionTrait as _;
+use siapla::holidays::{DEFAULT_OPEN_HOLIDAYS_URL, catalogue, set_global_open_holidays_url};
+use siapla_migration::MigratorTrait as _;
+use tracing_subscriber::EnvFilter;
+
+/// Maintain the offline catalogue of countries' subdivisions used to look up holiday regions
+#[derive(Parser, Debug)]
+#[command(name = "siapla-catalogue")]
+struct Args {
+    /// Database URL to use
+    #[arg(long, default_value = "sqlite:./run-data/test.sqlite")]
+    database_url: String,
+    /// Base url of the OpenHolidays API, e.g. a local stand-in server
+    #[arg(long, default_value = DEFAULT_OPEN_HOLIDAYS_URL)]
+    open_holidays_url: String,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Download the subdivisions of all supported countries
+    Refresh,
+    /// Import a catalogue file, e.g. one exported on a machine with network access
+    Import { file: PathBuf },
+    /// Export the stored catalogue to a file
+    Export { file: PathBuf },
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .compact()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info,sqlx=warn")),
+        )
+        .init();
+
+    let args = Args::parse();
+    set_global_open_holidays_url(args.open_holidays_url);
+    let db = sea_orm::Database::connect(&args.database_url).await?;
+    siapla_migration::Migrator::up(&db, None).await?;
+
+    match args.command {
+        Command::Refresh => {
+            let download = catalogue::download().await;
+            let txn = db.begin().await?;
+            let count = catalogue::store_catalogue(&txn, download.catalogue).await?;
+            txn.commit().await?;
+            println!("Stored {count} subdivisions");
+            if !download.failed.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "Failed to download the subdivisions of {}",
+                    download.failed.join(", ")
+                ));
+            }
+        }
+        Command::Import { file } => {
+            let txn = db.begin().await?;
+            let count = catalogue::import(&txn, &fs::read_to_string(file)?).await?;
+            txn.commit().await?;
+            println!("Stored {count} subdivisions");
+        }
+        Command::Export { file } => {
+            fs::write(file, catalogue::export(&db).await?)?;
+        }
+    }
+    Ok(())
+}
@@ -1,12 +1,15 @@
 use axum::body::Body;
-use axum::http::{Response as HttpResponse, StatusCode, header::CONTENT_TYPE};
+use axum::http::{
+    HeaderValue, Method, Response as HttpResponse, StatusCode,
+    header::{AUTHORIZATION, CONTENT_TYPE},
+};
 use axum::response::IntoResponse;
 use axum::{
     Extension, Router,
     extract::WebSocketUpgrade,
     middleware,
     response::Response,
-    routing::{MethodFilter, get, on},
+    routing::{MethodFilter, get, on, post},
 };
 use clap::Parser;
 use include_dir::{Dir, include_dir};
@@ -15,12 +18,15 @@ use juniper_axum::{
     extract::JuniperRequest, graphiql, playground, response::JuniperResponse, subscriptions,
 };
 use juniper_graphql_ws::ConnectionConfig;
+use sea_orm::TransactionTrait as _;
 use siapla::app_state::AppState;
+use siapla::auth::{create_admin, has_users, login, logout, set_global_auth_disabled};
 use siapla::gql::context::set_global_database_url;
+use siapla::holidays::{set_global_holiday_max_age, set_global_open_holidays_url};
 use siapla::{
     gql::{
         Schema,
-        context::{Context, add_context},
+        context::{Context, add_context, require_user},
     },
     scheduling::recalculate_loop,
 };
@@ -29,8 +35,8 @@ use sqlx::migrate::MigrateDatabase as _;
 use std::{net::SocketAddr, sync::Arc};
 use tokio::net::TcpListener;
 use tower::ServiceBuilder;
-use tower_http::cors::CorsLayer;
-use tracing::info;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::{info, warn};
 use tracing_subscriber::EnvFilter;
 // use juniper_graphql_ws::ConnectionConfig;
 // use tokio_stream::wrappers::IntervalStream;
@@ -52,21 +58,26 @@ pub async fn graphql(
 async fn custom_subscriptions(
     Extension(schema): Extension<Arc<Schema>>,
     Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<Arc<Context>>,
     ws: WebSocketUpgrade,
 ) -> Response {
+    // the connection outlives the request context, only keep the authenticated user
+    let user = context.user().cloned();
+    drop(context);
     ws.protocols(["graphql-transport-ws", "graphql-ws"])
         // .max_frame_size(1024)
         // .max_message_size(1024)
         // .max_write_buffer_size(100)
         .on_upgrade(move |socket| {
+            let context = Arc::try_unwrap(Context::new(app_state))
+                .expect("Arc has just been created, must be able to unwrap it.");
+            if let Some(user) = user {
+                context.set_user(user);
+            }
             subscriptions::serve_ws(
                 socket,
                 schema,
-                ConnectionConfig::new(
-                    Arc::try_unwrap(Context::new(app_state))
-                        .expect("Arc has just been created, must be able to unwrap it."),
-                )
-                .with_max_in_flight_operations(10),
+                ConnectionConfig::new(context).with_max_in_flight_operations(10),
             )
         })
 }
@@ -84,6 +95,24 @@ struct Args {
     /// Bind address e.g. 127.0.0.1:8880
     #[arg(long, default_value = "0.0.0.0:80")]
     bind: String,
+    /// Base url of the OpenHolidays API, e.g. a local stand-in server
+    #[arg(long, default_value = siapla::holidays::DEFAULT_OPEN_HOLIDAYS_URL)]
+    open_holidays_url: String,
+    /// Downloaded holidays older than this are refreshed when they are used
+    #[arg(long, default_value_t = siapla::holidays::DEFAULT_HOLIDAY_MAX_AGE_DAYS)]
+    holiday_max_age_days: i64,
+    /// Origin allowed to call the API from another site, e.g. http://localhost:9000 for the
+    /// frontend dev server. Can be repeated, without it only the bundled frontend may call the API.
+    #[arg(long = "allowed-origin")]
+    allowed_origins: Vec<String>,
+    /// Create an admin account with this name on startup unless it exists already. The password
+    /// is read from the SIAPLA_ADMIN_PASSWORD environment variable.
+    #[arg(long)]
+    create_admin: Option<String>,
+    /// Allow requests without login, with full admin rights. Only use this in trusted networks.
+    /// Without it, the server does not start before a user account exists.
+    #[arg(long)]
+    no_auth: bool,
 }
 
 fn file_response_from_dir(mut path: String) -> Response {
@@ -128,6 +157,21 @@ async fn init_db(db_url: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+async fn init_admin(db_url: &str, name: &str) -> anyhow::Result<()> {
+    let password = std::env::var("SIAPLA_ADMIN_PASSWORD").map_err(|_| {
+        anyhow::anyhow!("--create-admin needs the password in SIAPLA_ADMIN_PASSWORD")
+    })?;
+    let db = sea_orm::Database::connect(db_url).await?;
+    let txn = db.begin().await?;
+    if create_admin(&txn, name, &password).await? {
+        info!("created admin account '{name}'");
+    } else {
+        warn!("user '{name}' exists already, no admin account created");
+    }
+    txn.commit().await?;
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt()
@@ -142,7 +186,19 @@ async fn main() -> anyhow::Result<()> {
     // parse cli args and set global database url
     let args = Args::parse();
     init_db(&args.database_url).await?;
+    if let Some(name) = &args.create_admin {
+        init_admin(&args.database_url, name).await?;
+    }
+    if !args.no_auth && !has_users(&sea_orm::Database::connect(&args.database_url).await?).await? {
+        return Err(anyhow::anyhow!(
+            "No user account exists, create one with --create-admin (or allow requests without \
+             login in trusted networks with --no-auth)"
+        ));
+    }
+    set_global_auth_disabled(args.no_auth);
     set_global_database_url(args.database_url);
+    set_global_open_holidays_url(args.open_holidays_url);
+    set_global_holiday_max_age(chrono::TimeDelta::days(args.holiday_max_age_days));
 
     let (app_state, manual_rx) = AppState::new();
 
@@ -152,16 +208,30 @@ async fn main() -> anyhow::Result<()> {
     local_set.spawn_local(async move { recalculate_loop(app_state_for_loop, manual_rx).await });
     // tokio::spawn(recalculate_loop());
 
+    // same-origin only unless other origins are allowed explicitly
+    let allowed_origins = args
+        .allowed_origins
+        .iter()
+        .map(|origin| HeaderValue::from_str(origin))
+        .collect::<Result<Vec<_>, _>>()?;
     let cors = CorsLayer::new()
-        .allow_methods(tower_http::cors::Any)
-        .allow_headers(tower_http::cors::Any)
-        .allow_origin(tower_http::cors::Any);
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers([AUTHORIZATION, CONTENT_TYPE])
+        .allow_credentials(true)
+        .allow_origin(AllowOrigin::list(allowed_origins));
 
-    let app = Router::new()
+    // the API requires a login unless started with --no-auth
+    let api = Router::new()
         .route("/graphql", on(MethodFilter::GET.or(MethodFilter::POST), graphql))
         .route("/subscriptions", get(custom_subscriptions))
         .route("/graphiql", get(graphiql("/graphql", "/subscriptions")))
         .route("/playground", get(playground("/graphql", "/subscriptions")))
+        .route_layer(middleware::from_fn(require_user));
+
+    let app = Router::new()
+        .merge(api)
+        .route("/auth/login", post(login))
+        .route("/auth/logout", post(logout))
         // serve bundled frontend at root
         .route("/", get(|| async { file_response_from_dir("index.html".to_string()) }))
         .route(
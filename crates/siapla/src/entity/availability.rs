@@ -17,7 +17,9 @@ pub struct Model {
     pub id: i32,
     pub resource_id: i32,
     pub weekday: String,
-    pub duration: Decimal,
+    pub start: Time,
+    pub end: Time,
+    pub pattern_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -25,7 +27,9 @@ pub enum Column {
     Id,
     ResourceId,
     Weekday,
-    Duration,
+    Start,
+    End,
+    PatternId,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -52,7 +56,9 @@ impl ColumnTrait for Column {
             Self::Id => ColumnType::Integer.def(),
             Self::ResourceId => ColumnType::Integer.def(),
             Self::Weekday => ColumnType::String(StringLen::N(2u32)).def(),
-            Self::Duration => ColumnType::Decimal(None).def(),
+            Self::Start => ColumnType::Time.def(),
+            Self::End => ColumnType::Time.def(),
+            Self::PatternId => ColumnType::Integer.def().null(),
         }
     }
 }
@@ -19,6 +19,12 @@ pub struct Model {
     pub name: String,
     pub start: Option<Date>,
     pub end: Option<Date>,
+    pub school_start: Option<Date>,
+    pub school_end: Option<Date>,
+    pub provider: String,
+    pub global: bool,
+    pub language: String,
+    pub refreshed_at: Option<DateTimeUtc>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -28,6 +34,12 @@ pub enum Column {
     Name,
     Start,
     End,
+    SchoolStart,
+    SchoolEnd,
+    Provider,
+    Global,
+    Language,
+    RefreshedAt,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -46,6 +58,7 @@ impl PrimaryKeyTrait for PrimaryKey {
 pub enum Relation {
     HolidayEntry,
     Resource,
+    ResourceHoliday,
 }
 
 impl ColumnTrait for Column {
@@ -57,6 +70,12 @@ impl ColumnTrait for Column {
             Self::Name => ColumnType::String(StringLen::None).def(),
             Self::Start => ColumnType::Date.def().null(),
             Self::End => ColumnType::Date.def().null(),
+            Self::SchoolStart => ColumnType::Date.def().null(),
+            Self::SchoolEnd => ColumnType::Date.def().null(),
+            Self::Provider => ColumnType::String(StringLen::None).def(),
+            Self::Global => ColumnType::Boolean.def(),
+            Self::Language => ColumnType::String(StringLen::None).def(),
+            Self::RefreshedAt => ColumnType::Timestamp.def().null(),
         }
     }
 }
@@ -66,6 +85,7 @@ impl RelationTrait for Relation {
         match self {
             Self::HolidayEntry => Entity::has_many(super::holiday_entry::Entity).into(),
             Self::Resource => Entity::has_many(super::resource::Entity).into(),
+            Self::ResourceHoliday => Entity::has_many(super::resource_holiday::Entity).into(),
         }
     }
 }
@@ -82,4 +102,10 @@ impl Related<super::resource::Entity> for Entity {
     }
 }
 
+impl Related<super::resource_holiday::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ResourceHoliday.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
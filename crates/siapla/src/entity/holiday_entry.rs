@@ -12,12 +12,14 @@ impl EntityName for Entity {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, DeriveModel, DeriveActiveModel, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, DeriveModel, DeriveActiveModel, Serialize, Deserialize)]
 pub struct Model {
     pub id: i32,
     pub holiday_id: i32,
     pub name: Option<String>,
     pub date: Date,
+    pub fraction: f32,
+    pub r#type: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -26,6 +28,8 @@ pub enum Column {
     HolidayId,
     Name,
     Date,
+    Fraction,
+    Type,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -53,6 +57,8 @@ impl ColumnTrait for Column {
             Self::HolidayId => ColumnType::Integer.def(),
             Self::Name => ColumnType::String(StringLen::None).def().null(),
             Self::Date => ColumnType::Date.def(),
+            Self::Fraction => ColumnType::Float.def(),
+            Self::Type => ColumnType::String(StringLen::None).def(),
         }
     }
 }
@@ -4,13 +4,30 @@ pub mod prelude;
 
 pub mod allocated_resource;
 pub mod allocation;
+pub mod audit_log;
+pub mod auth_token;
 pub mod availability;
+pub mod availability_exception;
+pub mod availability_pattern;
+pub mod blocked_time;
+pub mod calculation_run;
+pub mod calculation_run_cost;
+pub mod change_set;
 pub mod dependency;
 pub mod holiday;
 pub mod holiday_entry;
 pub mod issue;
+pub mod milestone_contention;
+pub mod project;
+pub mod ramp_up_stage;
 pub mod resource;
 pub mod resource_constraint;
 pub mod resource_constraint_entry;
+pub mod resource_focus;
+pub mod resource_holiday;
+pub mod subdivision;
 pub mod task;
+pub mod team;
+pub mod team_membership;
+pub mod user;
 pub mod vacation;
@@ -2,13 +2,30 @@
 
 pub use super::allocated_resource::Entity as AllocatedResource;
 pub use super::allocation::Entity as Allocation;
+pub use super::audit_log::Entity as AuditLog;
+pub use super::auth_token::Entity as AuthToken;
 pub use super::availability::Entity as Availability;
+pub use super::availability_exception::Entity as AvailabilityException;
+pub use super::availability_pattern::Entity as AvailabilityPattern;
+pub use super::blocked_time::Entity as BlockedTime;
+pub use super::calculation_run::Entity as CalculationRun;
+pub use super::calculation_run_cost::Entity as CalculationRunCost;
+pub use super::change_set::Entity as ChangeSet;
 pub use super::dependency::Entity as Dependency;
 pub use super::holiday::Entity as Holiday;
 pub use super::holiday_entry::Entity as HolidayEntry;
 pub use super::issue::Entity as Issue;
+pub use super::milestone_contention::Entity as MilestoneContention;
+pub use super::project::Entity as Project;
+pub use super::ramp_up_stage::Entity as RampUpStage;
 pub use super::resource::Entity as Resource;
 pub use super::resource_constraint::Entity as ResourceConstraint;
 pub use super::resource_constraint_entry::Entity as ResourceConstraintEntry;
+pub use super::resource_focus::Entity as ResourceFocus;
+pub use super::resource_holiday::Entity as ResourceHoliday;
+pub use super::subdivision::Entity as Subdivision;
 pub use super::task::Entity as Task;
+pub use super::team::Entity as Team;
+pub use super::team_membership::Entity as TeamMembership;
+pub use super::user::Entity as User;
 pub use super::vacation::Entity as Vacation;
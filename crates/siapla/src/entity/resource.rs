@@ -20,6 +20,11 @@ pub struct Model {
     pub added: DateTimeUtc,
     pub removed: Option<DateTimeUtc>,
     pub holiday_id: Option<i32>,
+    pub school_holiday_id: Option<i32>,
+    pub school_holiday_mode: String,
+    pub placeholder: bool,
+    pub expected_start: Option<DateTimeUtc>,
+    pub version: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -30,6 +35,11 @@ pub enum Column {
     Added,
     Removed,
     HolidayId,
+    SchoolHolidayId,
+    SchoolHolidayMode,
+    Placeholder,
+    ExpectedStart,
+    Version,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -48,8 +58,15 @@ impl PrimaryKeyTrait for PrimaryKey {
 pub enum Relation {
     AllocatedResource,
     Availability,
+    AvailabilityException,
+    AvailabilityPattern,
+    BlockedTime,
     Holiday,
+    RampUpStage,
     ResourceConstraintEntry,
+    ResourceFocus,
+    ResourceHoliday,
+    TeamMembership,
     Vacation,
 }
 
@@ -63,6 +80,11 @@ impl ColumnTrait for Column {
             Self::Added => ColumnType::Timestamp.def(),
             Self::Removed => ColumnType::Timestamp.def().null(),
             Self::HolidayId => ColumnType::Integer.def().null(),
+            Self::SchoolHolidayId => ColumnType::Integer.def().null(),
+            Self::SchoolHolidayMode => ColumnType::String(StringLen::None).def(),
+            Self::Placeholder => ColumnType::Boolean.def(),
+            Self::ExpectedStart => ColumnType::Timestamp.def().null(),
+            Self::Version => ColumnType::Integer.def(),
         }
     }
 }
@@ -72,6 +94,13 @@ impl RelationTrait for Relation {
         match self {
             Self::AllocatedResource => Entity::has_many(super::allocated_resource::Entity).into(),
             Self::Availability => Entity::has_many(super::availability::Entity).into(),
+            Self::AvailabilityException => {
+                Entity::has_many(super::availability_exception::Entity).into()
+            }
+            Self::AvailabilityPattern => {
+                Entity::has_many(super::availability_pattern::Entity).into()
+            }
+            Self::BlockedTime => Entity::has_many(super::blocked_time::Entity).into(),
             Self::Holiday => Entity::belongs_to(super::holiday::Entity)
                 .from(Column::HolidayId)
                 .to(super::holiday::Column::Id)
@@ -79,6 +108,10 @@ impl RelationTrait for Relation {
             Self::ResourceConstraintEntry => {
                 Entity::has_many(super::resource_constraint_entry::Entity).into()
             }
+            Self::RampUpStage => Entity::has_many(super::ramp_up_stage::Entity).into(),
+            Self::ResourceFocus => Entity::has_many(super::resource_focus::Entity).into(),
+            Self::ResourceHoliday => Entity::has_many(super::resource_holiday::Entity).into(),
+            Self::TeamMembership => Entity::has_many(super::team_membership::Entity).into(),
             Self::Vacation => Entity::has_many(super::vacation::Entity).into(),
         }
     }
@@ -96,6 +129,24 @@ impl Related<super::availability::Entity> for Entity {
     }
 }
 
+impl Related<super::availability_exception::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AvailabilityException.def()
+    }
+}
+
+impl Related<super::availability_pattern::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AvailabilityPattern.def()
+    }
+}
+
+impl Related<super::blocked_time::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::BlockedTime.def()
+    }
+}
+
 impl Related<super::holiday::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Holiday.def()
@@ -108,6 +159,30 @@ impl Related<super::resource_constraint_entry::Entity> for Entity {
     }
 }
 
+impl Related<super::ramp_up_stage::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RampUpStage.def()
+    }
+}
+
+impl Related<super::resource_focus::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ResourceFocus.def()
+    }
+}
+
+impl Related<super::resource_holiday::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ResourceHoliday.def()
+    }
+}
+
+impl Related<super::team_membership::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TeamMembership.def()
+    }
+}
+
 impl Related<super::vacation::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Vacation.def()
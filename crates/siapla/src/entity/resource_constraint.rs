@@ -19,6 +19,7 @@ pub struct Model {
     pub r#type: String,
     pub optional: bool,
     pub speed: f32,
+    pub team_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -28,6 +29,7 @@ pub enum Column {
     Type,
     Optional,
     Speed,
+    TeamId,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -57,6 +59,7 @@ impl ColumnTrait for Column {
             Self::Type => ColumnType::String(StringLen::None).def(),
             Self::Optional => ColumnType::Boolean.def(),
             Self::Speed => ColumnType::Float.def(),
+            Self::TeamId => ColumnType::Integer.def().null(),
         }
     }
 }
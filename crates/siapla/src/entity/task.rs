@@ -22,6 +22,10 @@ pub struct Model {
     pub earliest_start: Option<DateTimeUtc>,
     pub schedule_target: Option<DateTimeUtc>,
     pub effort: Option<f32>,
+    pub split_policy: String,
+    pub max_interruptions: Option<i32>,
+    pub project_id: Option<i32>,
+    pub version: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -34,6 +38,10 @@ pub enum Column {
     EarliestStart,
     ScheduleTarget,
     Effort,
+    SplitPolicy,
+    MaxInterruptions,
+    ProjectId,
+    Version,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -68,6 +76,10 @@ impl ColumnTrait for Column {
             Self::EarliestStart => ColumnType::Timestamp.def().null(),
             Self::ScheduleTarget => ColumnType::Timestamp.def().null(),
             Self::Effort => ColumnType::Float.def().null(),
+            Self::SplitPolicy => ColumnType::String(StringLen::None).def(),
+            Self::MaxInterruptions => ColumnType::Integer.def().null(),
+            Self::ProjectId => ColumnType::Integer.def().null(),
+            Self::Version => ColumnType::Integer.def(),
         }
     }
 }
@@ -18,6 +18,7 @@ pub struct Model {
     pub resource_id: i32,
     pub from: DateTimeUtc,
     pub until: DateTimeUtc,
+    pub portion: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -26,6 +27,7 @@ pub enum Column {
     ResourceId,
     From,
     Until,
+    Portion,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -53,6 +55,7 @@ impl ColumnTrait for Column {
             Self::ResourceId => ColumnType::Integer.def(),
             Self::From => ColumnType::Timestamp.def(),
             Self::Until => ColumnType::Timestamp.def(),
+            Self::Portion => ColumnType::String(StringLen::None).def(),
         }
     }
 }
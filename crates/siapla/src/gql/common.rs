@@ -50,3 +50,63 @@ macro_rules! resolve_many_to_many {
 }
 
 pub(crate) use resolve_many_to_many;
+
+/// Saving an entity failed because it was changed after the client loaded it
+#[derive(thiserror::Error, Debug)]
+#[error("{entity} {id} was changed by someone else in the meantime, reload it and try again")]
+pub struct ConflictError {
+    pub entity: &'static str,
+    pub id: i32,
+    /// Version the changes are based on
+    pub expected_version: i32,
+    /// Version stored in the database
+    pub current_version: i32,
+}
+
+/// Reject changes based on an outdated version, saves without expected version always succeed
+pub fn check_version(
+    entity: &'static str,
+    id: i32,
+    expected_version: Option<i32>,
+    current_version: i32,
+) -> Result<(), ConflictError> {
+    match expected_version {
+        Some(expected_version) if expected_version != current_version => {
+            Err(ConflictError { entity, id, expected_version, current_version })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Convert the error of a save, conflicts get extensions the frontend can present, e.g.
+/// `{"code": "CONFLICT", "entity": "Task", "id": 1, "expectedVersion": 3, "currentVersion": 4}`
+pub fn save_error(err: anyhow::Error) -> juniper::FieldError {
+    let Some(conflict) = err.downcast_ref::<ConflictError>() else {
+        return juniper::IntoFieldError::into_field_error(err);
+    };
+    let mut extensions = juniper::Object::with_capacity(5);
+    extensions.add_field("code", juniper::Value::scalar("CONFLICT".to_owned()));
+    extensions.add_field("entity", juniper::Value::scalar(conflict.entity.to_owned()));
+    extensions.add_field("id", juniper::Value::scalar(conflict.id));
+    extensions.add_field("expectedVersion", juniper::Value::scalar(conflict.expected_version));
+    extensions.add_field("currentVersion", juniper::Value::scalar(conflict.current_version));
+    juniper::FieldError::new(conflict, juniper::Value::Object(extensions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_version() {
+        assert!(check_version("Task", 1, Some(3), 3).is_ok());
+        assert!(check_version("Task", 1, None, 3).is_ok());
+        let conflict = check_version("Task", 1, Some(2), 3).unwrap_err();
+        assert_eq!((conflict.expected_version, conflict.current_version), (2, 3));
+
+        let err = save_error(conflict.into());
+        let extensions = err.extensions().as_object_value().unwrap();
+        assert_eq!(extensions.get_field_value("code"), Some(&juniper::Value::scalar("CONFLICT")));
+        assert_eq!(extensions.get_field_value("currentVersion"), Some(&juniper::Value::scalar(3)));
+    }
+}
@@ -1,9 +1,10 @@
 use std::{
     env,
     marker::PhantomData,
-    sync::{Arc, Weak},
+    sync::{Arc, OnceLock, Weak},
 };
 
+use super::change_set::create_change_set;
 use super::dataloader::{AvailabilityBatcher, AvailabilityLoader, ByColBatcher, ByColLoader};
 use crate::scheduling::Intervals;
 use chrono::NaiveDateTime;
@@ -11,6 +12,8 @@ use chrono::NaiveDateTime;
 type AvailabilityLoaderMap =
     std::collections::HashMap<(NaiveDateTime, NaiveDateTime), AvailabilityLoader>;
 use crate::app_state::AppState;
+use crate::auth::{Role, auth_disabled, authenticate, request_token};
+use crate::entity::{audit_log, user};
 use axum::{extract::Request, http::StatusCode, middleware::Next, response::Response};
 use futures::{TryFutureExt, lock::Mutex};
 use sea_orm::{
@@ -33,6 +36,9 @@ pub struct Context {
     me: Weak<Self>,
     app_state: Arc<AppState>,
     success: Mutex<bool>,
+    user: OnceLock<user::Model>,
+    change_set: OnceCell<i32>,
+    changes: std::sync::Mutex<Vec<audit_log::Model>>,
 }
 
 impl std::fmt::Debug for Context {
@@ -40,6 +46,7 @@ impl std::fmt::Debug for Context {
         f.debug_struct("Context")
             .field("txn", &self.txn)
             .field("app_state", &self.app_state)
+            .field("user", &self.user.get().map(|u| &u.name))
             .finish()
     }
 }
@@ -72,9 +79,55 @@ impl Context {
             me: me.clone(),
             success: Mutex::new(true),
             app_state,
+            user: OnceLock::new(),
+            change_set: OnceCell::new(),
+            changes: Default::default(),
         })
     }
 
+    /// The authenticated user of the request, none if no user accounts exist yet
+    pub fn user(&self) -> Option<&user::Model> {
+        self.user.get()
+    }
+
+    pub fn set_user(&self, user: user::Model) {
+        let _ = self.user.set(user);
+    }
+
+    /// Role of the authenticated user. Without user (authentication disabled, background
+    /// calculation) everything is allowed.
+    pub fn role(&self) -> Role {
+        match self.user() {
+            None => Role::Admin,
+            Some(user) => user.role.parse().unwrap_or(Role::Viewer),
+        }
+    }
+
+    pub fn require_role(&self, role: Role) -> anyhow::Result<()> {
+        if self.role() < role {
+            let name: &'static str = role.into();
+            return Err(anyhow::anyhow!("Permission denied, this requires the {} role", name));
+        }
+        Ok(())
+    }
+
+    /// Whether private data of a resource (e.g. vacations) is visible: for planners and the
+    /// person using the resource
+    pub fn may_view_private(&self, resource_id: i32) -> bool {
+        self.role() >= Role::Planner
+            || self.user().is_some_and(|user| user.resource_id == Some(resource_id))
+    }
+
+    /// Change set grouping the undoable changes of this request, created on first use
+    pub async fn change_set(&self) -> anyhow::Result<i32> {
+        self.change_set.get_or_try_init(|| create_change_set(self)).await.copied()
+    }
+
+    /// Remember a change to publish it to subscribers once the transaction is committed
+    pub fn add_change(&self, change: audit_log::Model) {
+        self.changes.lock().expect("changes lock poisoned").push(change);
+    }
+
     pub async fn failed(&self) {
         let mut lock_guard = self.success.lock().await;
         *lock_guard = false;
@@ -248,12 +301,35 @@ pub async fn add_context(mut req: Request, next: Next) -> Result<Response, Statu
     let ctx_success: bool = *ctx.success.lock().await;
     if res.status().is_success() && ctx_success {
         ctx.commit().await.map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+        let changes = std::mem::take(ctx.changes.get_mut().expect("changes lock poisoned"));
+        ctx.app_state.publish_changes(changes);
     } else {
         ctx.rollback().await.map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
     }
     Ok(res)
 }
 
+/// Reject requests without a valid session or API token, unless authentication is disabled. Must
+/// run after `add_context`.
+pub async fn require_user(req: Request, next: Next) -> Result<Response, StatusCode> {
+    let ctx = req
+        .extensions()
+        .get::<Arc<Context>>()
+        .cloned()
+        .expect("Context must be provided by add_context");
+    let txn = ctx.txn().await.map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+    if let Some(token) = request_token(req.headers()) {
+        match authenticate(txn, &token).await.map_err(|_| StatusCode::SERVICE_UNAVAILABLE)? {
+            Some(user) => ctx.set_user(user),
+            None => return Err(StatusCode::UNAUTHORIZED),
+        }
+    } else if !auth_disabled() {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    drop(ctx);
+    Ok(next.run(req).await)
+}
+
 impl Context {
     pub fn app_state(&self) -> Arc<AppState> {
         Arc::clone(&self.app_state)
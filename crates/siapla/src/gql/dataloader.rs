@@ -1,23 +1,30 @@
 use std::{
     cmp::{max, min},
     collections::{HashMap, HashSet},
+    str::FromStr,
     sync::{Arc, Weak},
 };
 
 use dataloader::cached::Loader;
 use itertools::Itertools as _;
 use sea_orm::{
-    ColumnTrait, EntityTrait, ModelTrait, Order, QueryFilter, QueryOrder, strum::IntoEnumIterator,
+    ColumnTrait, Condition, EntityTrait, ModelTrait, Order, QueryFilter, QueryOrder,
+    strum::IntoEnumIterator,
 };
 
 use super::context::Context;
 use crate::SiaplaError;
 
-use crate::entity::{availability, resource, vacation};
+use crate::entity::{
+    availability, availability_exception, availability_pattern, blocked_time, holiday, resource,
+    resource_focus, resource_holiday, vacation,
+};
+use crate::gql::blocked_time::occurs_on;
+use crate::gql::holiday::{GQLHoliday, SchoolHolidayMode};
+use crate::gql::vacation::VacationPortion;
 use crate::scheduling::{Interval, Intervals};
-use chrono::{DateTime, Datelike, NaiveDateTime, NaiveTime, TimeDelta, Utc, Weekday};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Utc, Weekday};
 use chrono_tz::Tz;
-use sea_orm::prelude::Decimal;
 
 pub fn string_to_weekday(s: &str) -> anyhow::Result<Weekday> {
     match s {
@@ -32,12 +39,19 @@ pub fn string_to_weekday(s: &str) -> anyhow::Result<Weekday> {
     }
 }
 
+/// Working time blocks (local start, local end) per weekday, sorted by start
+pub type _WeekBlocks = HashMap<Weekday, Vec<(NaiveTime, NaiveTime)>>;
+
 pub struct _AvailabilityIterator {
     pub timezone: Tz,
     pub start: DateTime<Tz>,
     pub end: DateTime<Tz>,
-    pub durations: HashMap<Weekday, TimeDelta>,
-    pub last_end: Option<DateTime<Tz>>,
+    // default blocks, used on days not covered by any pattern
+    pub blocks: _WeekBlocks,
+    // date ranged patterns (valid_from, valid_until, blocks), latest valid_from first
+    pub patterns: Vec<(Option<NaiveDate>, Option<NaiveDate>, _WeekBlocks)>,
+    pub date: NaiveDate,
+    pub block_idx: usize,
 }
 
 impl _AvailabilityIterator {
@@ -46,73 +60,155 @@ impl _AvailabilityIterator {
         start: NaiveDateTime,
         end: NaiveDateTime,
         availabilities: Vec<&availability::Model>,
+        patterns: Vec<&availability_pattern::Model>,
     ) -> anyhow::Result<Self> {
         let tz: Tz = timezone.parse()?;
         let start_dt = DateTime::<Utc>::from_naive_utc_and_offset(start, Utc).with_timezone(&tz);
         let end_dt = DateTime::<Utc>::from_naive_utc_and_offset(end, Utc).with_timezone(&tz);
-        let durations = availabilities
+        let mut blocks: _WeekBlocks = HashMap::new();
+        let mut pattern_blocks: HashMap<i32, _WeekBlocks> = HashMap::new();
+        for a in availabilities {
+            let target = match a.pattern_id {
+                Some(pattern_id) => pattern_blocks.entry(pattern_id).or_default(),
+                None => &mut blocks,
+            };
+            target.entry(string_to_weekday(&a.weekday)?).or_default().push((a.start, a.end));
+        }
+        for b in blocks.values_mut().chain(pattern_blocks.values_mut().flat_map(|p| p.values_mut()))
+        {
+            b.sort();
+        }
+        let mut patterns = patterns
             .into_iter()
-            .map(|a| -> anyhow::Result<(Weekday, TimeDelta)> {
-                let mut secs = a.duration * Decimal::new(3600, 0);
-                secs.rescale(0); // rounding to whole seconds
-                Ok((string_to_weekday(&a.weekday)?, TimeDelta::seconds(secs.try_into()?)))
+            .map(|p| {
+                (p.valid_from, p.valid_until, pattern_blocks.remove(&p.id).unwrap_or_default())
             })
-            .collect::<anyhow::Result<_>>()?;
-        Ok(Self { timezone: tz, start: start_dt, end: end_dt, durations, last_end: None })
+            .collect::<Vec<_>>();
+        patterns.sort_by_key(|p| std::cmp::Reverse(p.0));
+        Ok(Self {
+            timezone: tz,
+            start: start_dt,
+            end: end_dt,
+            blocks,
+            patterns,
+            // start one day earlier: blocks ending on the next day may reach into the range
+            date: start_dt.date_naive() - TimeDelta::days(1),
+            block_idx: 0,
+        })
+    }
+
+    /// Blocks of the pattern valid on the given date
+    fn blocks_on(&self, date: NaiveDate) -> Option<&Vec<(NaiveTime, NaiveTime)>> {
+        let blocks = self
+            .patterns
+            .iter()
+            .find(|(from, until, _)| {
+                from.is_none_or(|from| from <= date) && until.is_none_or(|until| date <= until)
+            })
+            .map(|(_, _, blocks)| blocks)
+            .unwrap_or(&self.blocks);
+        blocks.get(&date.weekday())
     }
 }
 
+/// Local date and time to a timezone aware datetime. Times skipped by a DST change are moved
+/// to after the change.
+fn _local_datetime(tz: Tz, date: NaiveDate, time: NaiveTime) -> DateTime<Tz> {
+    let ndt = NaiveDateTime::new(date, time);
+    ndt.and_local_timezone(tz).earliest().unwrap_or_else(|| {
+        (ndt + TimeDelta::hours(1))
+            .and_local_timezone(tz)
+            .earliest()
+            .expect("Cannot determine local datetime")
+    })
+}
+
+/// Local block of working time on a date to UTC. Blocks ending at (or before) their start end
+/// on the next day, e.g. at 00:00.
+fn _local_block(
+    tz: Tz,
+    date: NaiveDate,
+    start: NaiveTime,
+    end: NaiveTime,
+) -> Interval<NaiveDateTime> {
+    let end_date = if end <= start { date + TimeDelta::days(1) } else { date };
+    Interval::new_lcro(
+        _local_datetime(tz, date, start).to_utc().naive_local(),
+        _local_datetime(tz, end_date, end).to_utc().naive_local(),
+    )
+}
+
+/// Part of the working time between the fractions `from` and `until` of its total length, e.g.
+/// (0.0, 0.5) for the first half.
+fn _working_time_portion(
+    working: &Intervals<NaiveDateTime>,
+    from: f64,
+    until: f64,
+) -> Intervals<NaiveDateTime> {
+    let total = working.length().unwrap_or_default().num_seconds() as f64;
+    let from = (total * from).round() as i64;
+    let until = (total * until).round() as i64;
+    let mut result = Intervals::new();
+    let mut offset = 0;
+    for iv in working {
+        let iv_start = iv.start().value().expect("no unbound intervals");
+        let length = iv.length().expect("no unbound intervals").num_seconds();
+        let start = max(from, offset) - offset;
+        let end = min(until, offset + length) - offset;
+        if start < end {
+            result.insert(Interval::new_lcro(
+                iv_start + TimeDelta::seconds(start),
+                iv_start + TimeDelta::seconds(end),
+            ));
+        }
+        offset += length;
+    }
+    result
+}
+
+/// Apply `_working_time_portion` to every (local) day within `range`.
+fn _daily_working_time_portion(
+    tz: Tz,
+    working: &Intervals<NaiveDateTime>,
+    range: Interval<NaiveDateTime>,
+    (from, until): (f64, f64),
+) -> Intervals<NaiveDateTime> {
+    let range_start = range.start().value().expect("no unbound intervals");
+    let range_end = range.end().value().expect("no unbound intervals");
+    let working = working.intersection(&range.into());
+    let mut result = Intervals::new();
+    let mut date = range_start.and_utc().with_timezone(&tz).date_naive();
+    while date <= range_end.and_utc().with_timezone(&tz).date_naive() {
+        let day = _local_block(tz, date, NaiveTime::MIN, NaiveTime::MIN);
+        result =
+            result.union(&_working_time_portion(&working.intersection(&day.into()), from, until));
+        date += TimeDelta::days(1);
+    }
+    result
+}
+
 impl Iterator for _AvailabilityIterator {
     type Item = Interval<NaiveDateTime>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut date =
-            self.last_end.map(|e| e + TimeDelta::days(1)).unwrap_or(self.start).date_naive();
         loop {
-            if date > self.end.date_naive() {
-                self.last_end = Some(self.end);
+            if self.date > self.end.date_naive() {
                 return None;
             }
-            if let Some(dur) = self.durations.get(&date.weekday()) {
-                let secs = std::cmp::min(dur.num_seconds() / 2, 12 * 3600);
-                if secs <= 0 {
-                    date += TimeDelta::days(1);
-                    continue;
-                }
-                let i_start = std::cmp::max(
-                    NaiveDateTime::new(
-                        date,
-                        NaiveTime::from_num_seconds_from_midnight_opt(12 * 3600 - secs as u32, 0)
-                            .unwrap(),
-                    )
-                    .and_local_timezone(self.timezone.clone())
-                    .latest()
-                    .expect("Cannot determine availability start"),
-                    self.start,
-                );
-                let i_end = std::cmp::min(
-                    NaiveDateTime::new(
-                        date,
-                        NaiveTime::from_num_seconds_from_midnight_opt(12 * 3600 + secs as u32, 0)
-                            .unwrap(),
-                    )
-                    .and_local_timezone(self.timezone.clone())
-                    .earliest()
-                    .expect("Cannot determine availability end"),
-                    self.end,
-                );
-                self.last_end = Some(i_end);
-                if i_end <= i_start {
-                    date += TimeDelta::days(1);
-                    continue;
-                }
-                return Some(Interval::new_lcro(
-                    i_start.to_utc().naive_local(),
-                    i_end.to_utc().naive_local(),
-                ));
-            } else {
-                date += TimeDelta::days(1);
+            let block = self.blocks_on(self.date).and_then(|b| b.get(self.block_idx)).copied();
+            let Some((b_start, b_end)) = block else {
+                self.date += TimeDelta::days(1);
+                self.block_idx = 0;
+                continue;
+            };
+            self.block_idx += 1;
+            let block = _local_block(self.timezone, self.date, b_start, b_end);
+            let i_start = max(block.start().value()?, self.start.naive_utc());
+            let i_end = min(block.end().value()?, self.end.naive_utc());
+            if i_end <= i_start {
+                continue;
             }
+            return Some(Interval::new_lcro(i_start, i_end));
         }
     }
 }
@@ -131,6 +227,10 @@ pub async fn query_combined_availability(
         .filter(availability::Column::ResourceId.is_in(id_set.clone()))
         .all(db)
         .await?;
+    let db_patterns = availability_pattern::Entity::find()
+        .filter(availability_pattern::Column::ResourceId.is_in(id_set.clone()))
+        .all(db)
+        .await?;
     let db_vacations = vacation::Entity::find()
         .filter(vacation::Column::ResourceId.is_in(id_set.clone()))
         .filter(vacation::Column::From.lt(end))
@@ -138,16 +238,51 @@ pub async fn query_combined_availability(
         .order_by(vacation::Column::From, Order::Asc)
         .all(db)
         .await?;
+    // dates are local to the resources' timezones, add a day of margin on both sides
+    let db_exceptions = availability_exception::Entity::find()
+        .filter(availability_exception::Column::ResourceId.is_in(id_set.clone()))
+        .filter(availability_exception::Column::Date.gte(start.date() - TimeDelta::days(1)))
+        .filter(availability_exception::Column::Date.lte(end.date() + TimeDelta::days(1)))
+        .all(db)
+        .await?;
+    let db_blocked_times = blocked_time::Entity::find()
+        .filter(blocked_time::Column::ResourceId.is_in(id_set.clone()))
+        .all(db)
+        .await?;
     let db_resources = resource::Entity::find()
         .filter(resource::Column::Id.is_in(resource_ids.clone()))
         .all(db)
         .await?;
+    let db_holiday_links = resource_holiday::Entity::find()
+        .filter(resource_holiday::Column::ResourceId.is_in(id_set.clone()))
+        .all(db)
+        .await?;
+    // regional, additional and global holiday calendars
+    let holiday_ids = db_resources
+        .iter()
+        .filter_map(|r| r.holiday_id)
+        .chain(db_holiday_links.iter().map(|l| l.holiday_id))
+        .unique()
+        .collect::<Vec<_>>();
+    let db_holidays = holiday::Entity::find()
+        .filter(
+            Condition::any()
+                .add(holiday::Column::Global.eq(true))
+                .add(holiday::Column::Id.is_in(holiday_ids)),
+        )
+        .all(db)
+        .await?;
     let res_map = db_resources.into_iter().map(|r| (r.id, r)).collect::<HashMap<i32, _>>();
 
     let mut results: Vec<Intervals<NaiveDateTime>> = Vec::with_capacity(resource_ids.len());
     for &rid in resource_ids.iter() {
         let db_res = res_map.get(&rid).expect("Resource must exist");
+        // planned hires are not available before their expected start
         let res_start = max(start, db_res.added.naive_utc());
+        let res_start = match db_res.expected_start {
+            Some(expected_start) => max(res_start, expected_start.naive_utc()),
+            None => res_start,
+        };
         let res_end = match db_res.removed {
             Some(removed) => min(end, removed.naive_utc()),
             None => end,
@@ -157,48 +292,238 @@ pub async fn query_combined_availability(
             res_start,
             res_end,
             db_availabilities.iter().filter(|a| a.resource_id == rid).collect(),
+            db_patterns.iter().filter(|p| p.resource_id == rid).collect(),
         )?;
 
-        let holiday_intervals = match db_res.holiday(ctx).await? {
-            Some(h) => h
+        let tz = availability_iter.timezone;
+        let calendars = db_holidays.iter().filter(|h| {
+            h.global
+                || db_res.holiday_id == Some(h.id)
+                || db_holiday_links.iter().any(|l| l.resource_id == rid && l.holiday_id == h.id)
+        });
+        let mut holiday_entries = vec![];
+        for calendar in calendars {
+            let entries = GQLHoliday::from_model(calendar.clone())
                 .entries(
                     ctx,
                     availability_iter.start.date_naive(),
                     availability_iter.end.date_naive(),
                 )
+                .await?;
+            holiday_entries.extend(entries);
+        }
+        let school_holiday_entries = match db_res.school_holiday(ctx).await? {
+            Some(h) => {
+                h.school_entries(
+                    ctx,
+                    availability_iter.start.date_naive(),
+                    availability_iter.end.date_naive(),
+                )
                 .await?
-                .into_iter()
-                .map(|he| {
-                    let start = NaiveDateTime::new(
-                        he.date,
-                        NaiveTime::from_hms_opt(0, 0, 0).expect("Must be a valid time"),
-                    )
-                    .and_local_timezone(availability_iter.timezone)
-                    .earliest()
-                    .expect("Cannot determine holidays datetime.")
-                    .to_utc()
-                    .naive_local();
-                    let end = start + TimeDelta::hours(24);
-                    Interval::new_lcro(start, end)
-                })
-                .collect(),
-            None => Intervals::new(),
+            }
+            None => vec![],
         };
+        let (first_date, last_date) =
+            (availability_iter.start.date_naive(), availability_iter.end.date_naive());
+        let availability_intervals: Intervals<NaiveDateTime> = availability_iter.collect();
+
+        // partial holidays take off the end of the day's working time
+        let holiday_intervals: Intervals<NaiveDateTime> = holiday_entries
+            .iter()
+            .flat_map(|he| {
+                let day = _local_block(tz, he.date, NaiveTime::MIN, NaiveTime::MIN);
+                if he.fraction >= 1.0 {
+                    day.into()
+                } else {
+                    let working = availability_intervals.intersection(&day.into());
+                    _working_time_portion(&working, 1.0 - he.fraction as f64, 1.0)
+                }
+            })
+            .collect();
 
         let vacation_intervals: Intervals<NaiveDateTime> = db_vacations
             .iter()
-            .filter(|v| v.resource_id == rid)
-            .map(|v| Interval::new_lcro(v.from.naive_local(), v.until.naive_local()))
+            .filter(|v| v.resource_id == rid && v.from < v.until)
+            .flat_map(|v| {
+                let range = Interval::new_lcro(v.from.naive_utc(), v.until.naive_utc());
+                let portion = match VacationPortion::from_str(&v.portion) {
+                    Ok(VacationPortion::Morning) => (0.0, 0.5),
+                    Ok(VacationPortion::Afternoon) => (0.5, 1.0),
+                    _ => return range.into(),
+                };
+                _daily_working_time_portion(tz, &availability_intervals, range, portion)
+            })
             .collect();
 
-        let availability_intervals: Intervals<NaiveDateTime> = availability_iter.collect();
-        let intervals =
-            availability_intervals.difference(&vacation_intervals).difference(&holiday_intervals);
+        // school holidays either count as vacation or take off the second half of the day
+        let school_holiday_mode = SchoolHolidayMode::from_str(&db_res.school_holiday_mode)
+            .unwrap_or(SchoolHolidayMode::Reduced);
+        let school_holiday_intervals: Intervals<NaiveDateTime> = school_holiday_entries
+            .iter()
+            .flat_map(|he| {
+                let day = _local_block(tz, he.date, NaiveTime::MIN, NaiveTime::MIN);
+                match school_holiday_mode {
+                    SchoolHolidayMode::Vacation => day.into(),
+                    SchoolHolidayMode::Reduced => {
+                        let working = availability_intervals.intersection(&day.into());
+                        _working_time_portion(&working, 0.5, 1.0)
+                    }
+                }
+            })
+            .collect();
+
+        let resource_blocked_times: Vec<_> =
+            db_blocked_times.iter().filter(|b| b.resource_id == rid).collect();
+        let mut blocked_intervals = Intervals::new();
+        if !resource_blocked_times.is_empty() {
+            let mut date = first_date;
+            while date <= last_date {
+                for b in resource_blocked_times.iter().filter(|b| occurs_on(b, date)) {
+                    blocked_intervals.insert(_local_block(tz, date, b.start, b.end));
+                }
+                date += TimeDelta::days(1);
+            }
+        }
+
+        // exceptions replace the working time of their whole (local) day
+        let resource_exceptions: Vec<_> =
+            db_exceptions.iter().filter(|e| e.resource_id == rid).collect();
+        let exception_days: Intervals<NaiveDateTime> = resource_exceptions
+            .iter()
+            .map(|e| _local_block(tz, e.date, NaiveTime::MIN, NaiveTime::MIN))
+            .collect();
+        let exception_intervals: Intervals<NaiveDateTime> = resource_exceptions
+            .iter()
+            .filter_map(|e| match (e.start, e.end) {
+                (Some(start), Some(end)) => Some(_local_block(tz, e.date, start, end)),
+                _ => None,
+            })
+            .collect();
+        let resource_range: Intervals<NaiveDateTime> = if res_start < res_end {
+            Interval::new_lcro(res_start, res_end).into()
+        } else {
+            Intervals::new()
+        };
+
+        let intervals = availability_intervals
+            .difference(&vacation_intervals)
+            .difference(&holiday_intervals)
+            .difference(&school_holiday_intervals)
+            .difference(&blocked_intervals)
+            .difference(&exception_days)
+            .union(&exception_intervals.intersection(&resource_range));
         results.push(intervals);
     }
     Ok(results)
 }
 
+/// Query the regular weekly working time for a list of resources, ignoring holidays, vacations
+/// and the resources' added / removed dates.
+/// Returns a vector of `Intervals<NaiveDateTime>` in the same order as `resource_ids`.
+pub async fn query_regular_availability(
+    ctx: &Context,
+    resource_ids: &[i32],
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> anyhow::Result<Vec<Intervals<NaiveDateTime>>> {
+    let db = ctx.txn().await?;
+    let db_availabilities = availability::Entity::find()
+        .filter(availability::Column::ResourceId.is_in(resource_ids.to_vec()))
+        .all(db)
+        .await?;
+    let db_patterns = availability_pattern::Entity::find()
+        .filter(availability_pattern::Column::ResourceId.is_in(resource_ids.to_vec()))
+        .all(db)
+        .await?;
+    let db_resources = resource::Entity::find()
+        .filter(resource::Column::Id.is_in(resource_ids.to_vec()))
+        .all(db)
+        .await?;
+    let res_map = db_resources.into_iter().map(|r| (r.id, r)).collect::<HashMap<i32, _>>();
+
+    let mut results: Vec<Intervals<NaiveDateTime>> = Vec::with_capacity(resource_ids.len());
+    for &rid in resource_ids.iter() {
+        let db_res = res_map.get(&rid).expect("Resource must exist");
+        let availability_iter = _AvailabilityIterator::new(
+            &db_res.timezone,
+            start,
+            end,
+            db_availabilities.iter().filter(|a| a.resource_id == rid).collect(),
+            db_patterns.iter().filter(|p| p.resource_id == rid).collect(),
+        )?;
+        results.push(availability_iter.collect());
+    }
+    Ok(results)
+}
+
+/// Share of a resource's availability spent on planned tasks
+pub struct FocusFactors {
+    pub timezone: Tz,
+    // (valid_from, valid_until, factor), latest valid_from first
+    pub factors: Vec<(Option<NaiveDate>, Option<NaiveDate>, f64)>,
+}
+
+impl FocusFactors {
+    /// Factor valid on the given (local) date, 1.0 if none is set
+    pub fn factor_on(&self, date: NaiveDate) -> f64 {
+        self.factors
+            .iter()
+            .find(|(from, until, _)| {
+                from.is_none_or(|from| from <= date) && until.is_none_or(|until| date <= until)
+            })
+            .map_or(1.0, |(_, _, factor)| *factor)
+    }
+
+    /// Effective working time: the first part (according to the factor) of every (local) day's
+    /// working time.
+    pub fn apply(&self, working: &Intervals<NaiveDateTime>) -> Intervals<NaiveDateTime> {
+        let Some(hull) = working.hull() else {
+            return Intervals::new();
+        };
+        let to_local = |dt: NaiveDateTime| dt.and_utc().with_timezone(&self.timezone).date_naive();
+        let mut result = Intervals::new();
+        let mut date = to_local(hull.start().value().expect("no unbound intervals"));
+        while date <= to_local(hull.end().value().expect("no unbound intervals")) {
+            let day = _local_block(self.timezone, date, NaiveTime::MIN, NaiveTime::MIN);
+            let day_working = working.intersection(&day.into());
+            result = result.union(&_working_time_portion(&day_working, 0.0, self.factor_on(date)));
+            date += TimeDelta::days(1);
+        }
+        result
+    }
+}
+
+/// Query the focus factors for a list of resources.
+/// Returns a vector of `FocusFactors` in the same order as `resource_ids`.
+pub async fn query_focus_factors(
+    ctx: &Context,
+    resource_ids: &[i32],
+) -> anyhow::Result<Vec<FocusFactors>> {
+    let db = ctx.txn().await?;
+    let db_focus = resource_focus::Entity::find()
+        .filter(resource_focus::Column::ResourceId.is_in(resource_ids.to_vec()))
+        .all(db)
+        .await?;
+    let db_resources = resource::Entity::find()
+        .filter(resource::Column::Id.is_in(resource_ids.to_vec()))
+        .all(db)
+        .await?;
+    let res_map = db_resources.into_iter().map(|r| (r.id, r)).collect::<HashMap<i32, _>>();
+
+    let mut results = Vec::with_capacity(resource_ids.len());
+    for &rid in resource_ids.iter() {
+        let db_res = res_map.get(&rid).expect("Resource must exist");
+        let mut factors = db_focus
+            .iter()
+            .filter(|f| f.resource_id == rid)
+            .map(|f| (f.valid_from, f.valid_until, f.factor as f64))
+            .collect::<Vec<_>>();
+        factors.sort_by_key(|f| std::cmp::Reverse(f.0));
+        results.push(FocusFactors { timezone: db_res.timezone.parse()?, factors });
+    }
+    Ok(results)
+}
+
 pub struct AvailabilityBatcher {
     pub ctx: Weak<Context>,
     pub start: NaiveDateTime,
@@ -291,3 +616,43 @@ pub type ByColLoader<ET, const CIDX: usize> = Loader<
     Result<Vec<<ET as EntityTrait>::Model>, Arc<anyhow::Error>>,
     ByColBatcher<ET, CIDX>,
 >;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ndt(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{date} {time}"), "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_focus_factors_apply() {
+        let focus = FocusFactors {
+            timezone: chrono_tz::Europe::Berlin,
+            factors: vec![(Some(date("2023-01-03")), None, 0.5), (None, None, 0.75)],
+        };
+        assert_eq!(focus.factor_on(date("2023-01-02")), 0.75);
+        assert_eq!(focus.factor_on(date("2023-01-03")), 0.5);
+        assert_eq!(
+            FocusFactors { timezone: Tz::UTC, factors: vec![] }.factor_on(date("2023-01-03")),
+            1.0
+        );
+
+        // 09:00 - 13:00 and 14:00 - 18:00 local time (UTC+1)
+        let mut working = Intervals::new();
+        for day in ["2023-01-02", "2023-01-03"] {
+            working.insert(Interval::new_lcro(ndt(day, "08:00"), ndt(day, "12:00")));
+            working.insert(Interval::new_lcro(ndt(day, "13:00"), ndt(day, "17:00")));
+        }
+        let mut expected = Intervals::new();
+        expected.insert(Interval::new_lcro(ndt("2023-01-02", "08:00"), ndt("2023-01-02", "12:00")));
+        expected.insert(Interval::new_lcro(ndt("2023-01-02", "13:00"), ndt("2023-01-02", "15:00")));
+        expected.insert(Interval::new_lcro(ndt("2023-01-03", "08:00"), ndt("2023-01-03", "12:00")));
+        assert_eq!(focus.apply(&working), expected);
+        assert_eq!(focus.apply(&Intervals::new()), Intervals::new());
+    }
+}
@@ -6,7 +6,11 @@ pub mod query;
 pub mod subscription;
 mod types;
 
-pub use types::{allocation, availability, holiday, issue, plan, resource, task, vacation};
+pub use types::{
+    allocation, audit_log, availability, availability_exception, blocked_time, calculation_run,
+    change_set, holiday, issue, plan, project, ramp_up_stage, resource, resource_focus, task, team,
+    user, vacation,
+};
 
 use juniper::*;
 
@@ -1,14 +1,35 @@
-use juniper::graphql_object;
+use juniper::{FieldResult, graphql_object};
 use sea_orm::ActiveModelTrait;
 use sea_orm::{ActiveValue, prelude::*};
 
-use crate::entity::{allocated_resource, allocation};
-use crate::entity::{resource, task};
+use crate::auth::Role;
+use crate::entity::{allocated_resource, allocation, change_set, holiday, holiday_entry, project};
+use crate::entity::{resource, resource_constraint, resource_constraint_entry, task, team, user};
 
 use super::{
+    audit_log::{
+        AuditEntityType, booking_snapshot, holiday_snapshot, record_change, resource_snapshot,
+        team_snapshot,
+    },
+    change_set::{redo, undo},
+    common::save_error,
     context::Context,
+    holiday::{
+        GQLCatalogueRefresh, GQLHoliday, HolidayEntryType, holiday_create_manual,
+        holiday_entry_add, holiday_entry_delete, holiday_entry_set_fraction, holiday_import_ics,
+        holiday_refresh, record_holiday_change,
+    },
+    project::{
+        ProjectSaveInput, project_save, touch_project, touch_resource_projects,
+        touch_task_projects, touch_team_projects,
+    },
     resource::{ResourceSaveInput, resource_save},
-    task::{TaskSaveInput, task_save},
+    task::{TaskSaveInput, delete_tasks, task_save},
+    team::{TeamSaveInput, team_save},
+    user::{
+        GQLCreatedApiToken, UserSaveInput, api_token_create, api_token_delete, user_delete,
+        user_save,
+    },
 };
 
 #[derive(Default)]
@@ -21,12 +42,12 @@ impl Mutation {
         Default::default()
     }
 
-    async fn task_save(ctx: &Context, task: TaskSaveInput) -> anyhow::Result<task::Model> {
+    async fn task_save(ctx: &Context, task: TaskSaveInput) -> FieldResult<task::Model> {
         let res = match task_save(ctx, task).await {
             Ok(res) => res,
             Err(err) => {
                 ctx.failed().await;
-                Err(err)?
+                return Err(save_error(err));
             }
         };
         // notify modification channel
@@ -35,8 +56,47 @@ impl Mutation {
     }
 
     async fn task_delete(ctx: &Context, task_id: i32) -> anyhow::Result<bool> {
+        ctx.require_role(Role::Planner)?;
         let txn = ctx.txn().await?;
-        let am = task::ActiveModel { id: sea_orm::ActiveValue::Set(task_id), ..Default::default() };
+        let Some(task) = task::Entity::find_by_id(task_id).one(txn).await? else {
+            return Ok(false);
+        };
+        if let Some(project_id) = task.project_id {
+            touch_project(txn, project_id).await?;
+        }
+        let ok = delete_tasks(ctx, vec![task]).await? > 0;
+        if ok {
+            ctx.app_state().notify_modified("graphql".to_string());
+        }
+        Ok(ok)
+    }
+
+    async fn project_save(
+        ctx: &Context,
+        project: ProjectSaveInput,
+    ) -> anyhow::Result<project::Model> {
+        ctx.require_role(Role::Planner)?;
+        let res = match project_save(ctx, project).await {
+            Ok(res) => res,
+            Err(err) => {
+                ctx.failed().await;
+                Err(err)?
+            }
+        };
+        ctx.app_state().notify_modified("graphql".to_string());
+        Ok(res)
+    }
+
+    /// Delete a project including all of its tasks
+    async fn project_delete(ctx: &Context, db_id: i32) -> anyhow::Result<bool> {
+        ctx.require_role(Role::Planner)?;
+        let txn = ctx.txn().await?;
+        // project_id has no foreign key (SQLite cannot add them to existing tables)
+        let tasks = task::Entity::find().filter(task::Column::ProjectId.eq(db_id)).all(txn).await?;
+        delete_tasks(ctx, tasks).await?;
+        let before = project::Entity::find_by_id(db_id).one(txn).await?;
+        record_change(ctx, AuditEntityType::Project, db_id, before.as_ref(), None).await?;
+        let am = project::ActiveModel { id: ActiveValue::Set(db_id), ..Default::default() };
         let res = am.delete(txn).await?;
         let ok = res.rows_affected > 0;
         if ok {
@@ -48,12 +108,12 @@ impl Mutation {
     async fn resource_save(
         ctx: &Context,
         resource: ResourceSaveInput,
-    ) -> anyhow::Result<resource::Model> {
+    ) -> FieldResult<resource::Model> {
         let res = match resource_save(ctx, resource).await {
             Ok(res) => res,
             Err(err) => {
                 ctx.failed().await;
-                Err(err)?
+                return Err(save_error(err));
             }
         };
         ctx.app_state().notify_modified("graphql".to_string());
@@ -61,7 +121,17 @@ impl Mutation {
     }
 
     async fn resource_delete(ctx: &Context, resource_id: i32) -> anyhow::Result<bool> {
+        ctx.require_role(Role::Planner)?;
         let txn = ctx.txn().await?;
+        // user.resource_id has no foreign key (SQLite cannot add them to existing tables)
+        user::Entity::update_many()
+            .col_expr(user::Column::ResourceId, Expr::value(Option::<i32>::None))
+            .filter(user::Column::ResourceId.eq(resource_id))
+            .exec(txn)
+            .await?;
+        touch_resource_projects(txn, resource_id).await?;
+        let before = resource_snapshot(txn, resource_id).await?;
+        record_change(ctx, AuditEntityType::Resource, resource_id, before.as_ref(), None).await?;
         let am = resource::ActiveModel {
             id: sea_orm::ActiveValue::Set(resource_id),
             ..Default::default()
@@ -74,6 +144,53 @@ impl Mutation {
         Ok(ok)
     }
 
+    async fn team_save(ctx: &Context, team: TeamSaveInput) -> anyhow::Result<team::Model> {
+        ctx.require_role(Role::Planner)?;
+        let res = match team_save(ctx, team).await {
+            Ok(res) => res,
+            Err(err) => {
+                ctx.failed().await;
+                Err(err)?
+            }
+        };
+        ctx.app_state().notify_modified("graphql".to_string());
+        Ok(res)
+    }
+
+    /// Delete a team including its membership history. Constraints targeting only this team are
+    /// removed, others keep their resource entries.
+    async fn team_delete(ctx: &Context, db_id: i32) -> anyhow::Result<bool> {
+        ctx.require_role(Role::Planner)?;
+        let txn = ctx.txn().await?;
+        touch_team_projects(txn, db_id).await?;
+        // team_id has no foreign key (SQLite cannot add them to existing tables)
+        let constraints = resource_constraint::Entity::find()
+            .filter(resource_constraint::Column::TeamId.eq(db_id))
+            .find_with_related(resource_constraint_entry::Entity)
+            .all(txn)
+            .await?;
+        let (unused, used): (Vec<_>, Vec<_>) =
+            constraints.into_iter().partition(|(_, entries)| entries.is_empty());
+        resource_constraint::Entity::delete_many()
+            .filter(resource_constraint::Column::Id.is_in(unused.iter().map(|(c, _)| c.id)))
+            .exec(txn)
+            .await?;
+        resource_constraint::Entity::update_many()
+            .col_expr(resource_constraint::Column::TeamId, Expr::value(Option::<i32>::None))
+            .filter(resource_constraint::Column::Id.is_in(used.iter().map(|(c, _)| c.id)))
+            .exec(txn)
+            .await?;
+        let before = team_snapshot(txn, db_id).await?;
+        record_change(ctx, AuditEntityType::Team, db_id, before.as_ref(), None).await?;
+        let am = team::ActiveModel { id: ActiveValue::Set(db_id), ..Default::default() };
+        let res = am.delete(txn).await?;
+        let ok = res.rows_affected > 0;
+        if ok {
+            ctx.app_state().notify_modified("graphql".to_string());
+        }
+        Ok(ok)
+    }
+
     async fn booking_save(
         ctx: &Context,
         db_id: Option<i32>,
@@ -84,6 +201,17 @@ impl Mutation {
         r#final: bool,
     ) -> anyhow::Result<allocation::Model> {
         let txn = ctx.txn().await?;
+        check_booking_access(ctx, db_id, Some(&resources)).await?;
+        let before = match db_id {
+            Some(id) => booking_snapshot(txn, id).await?,
+            None => None,
+        };
+        // the booking may be moved to another task, both plans change
+        let previous_task = match db_id {
+            Some(id) => allocation::Entity::find_by_id(id).one(txn).await?.map(|a| a.task_id),
+            None => None,
+        };
+        touch_task_projects(txn, previous_task.into_iter().chain([task_id]).collect()).await?;
         // upsert allocation
         let db_alloc = if let Some(id) = db_id {
             let am = allocation::ActiveModel {
@@ -124,12 +252,21 @@ impl Mutation {
             };
             arm.insert(txn).await?;
         }
+        let after = booking_snapshot(txn, db_alloc.id).await?;
+        record_change(ctx, AuditEntityType::Booking, db_alloc.id, before.as_ref(), after.as_ref())
+            .await?;
         ctx.app_state().notify_modified("graphql".to_string());
         Ok(db_alloc)
     }
 
     async fn booking_delete(ctx: &Context, db_id: i32) -> anyhow::Result<bool> {
         let txn = ctx.txn().await?;
+        check_booking_access(ctx, Some(db_id), None).await?;
+        if let Some(booking) = allocation::Entity::find_by_id(db_id).one(txn).await? {
+            touch_task_projects(txn, vec![booking.task_id]).await?;
+        }
+        let before = booking_snapshot(txn, db_id).await?;
+        record_change(ctx, AuditEntityType::Booking, db_id, before.as_ref(), None).await?;
         allocated_resource::Entity::delete_many()
             .filter(allocated_resource::Column::AllocationId.eq(db_id))
             .exec(txn)
@@ -144,9 +281,324 @@ impl Mutation {
         Ok(ok)
     }
 
+    /// Set the part of the working day that is off on a holiday, e.g. 0.5 for half a day
+    async fn holiday_entry_set_fraction(
+        ctx: &Context,
+        db_id: i32,
+        fraction: f64,
+    ) -> anyhow::Result<holiday_entry::Model> {
+        ctx.require_role(Role::Admin)?;
+        let res = match holiday_entry_set_fraction(ctx, db_id, fraction).await {
+            Ok(res) => res,
+            Err(err) => {
+                ctx.failed().await;
+                Err(err)?
+            }
+        };
+        ctx.app_state().notify_modified("graphql".to_string());
+        Ok(res)
+    }
+
+    /// Import a holiday calendar from the content of an iCalendar (`.ics`) file. If `dbId` is
+    /// given, the entries of that calendar are replaced, otherwise a new calendar is created.
+    async fn holiday_import_ics(
+        ctx: &Context,
+        db_id: Option<i32>,
+        name: String,
+        content: String,
+        entry_type: Option<HolidayEntryType>,
+    ) -> anyhow::Result<GQLHoliday> {
+        ctx.require_role(Role::Admin)?;
+        let res = match holiday_import_ics(ctx, db_id, name, content, entry_type).await {
+            Ok(res) => res,
+            Err(err) => {
+                ctx.failed().await;
+                Err(err)?
+            }
+        };
+        ctx.app_state().notify_modified("graphql".to_string());
+        Ok(res)
+    }
+
+    /// Download the entries of a holiday calendar again, e.g. to pick up corrections. If a
+    /// language is given, it is stored with the calendar and used for the entry names.
+    async fn holiday_refresh(
+        ctx: &Context,
+        db_id: i32,
+        language: Option<String>,
+    ) -> anyhow::Result<GQLHoliday> {
+        ctx.require_role(Role::Admin)?;
+        let res = match holiday_refresh(ctx, db_id, language).await {
+            Ok(res) => res,
+            Err(err) => {
+                ctx.failed().await;
+                Err(err)?
+            }
+        };
+        ctx.app_state().notify_modified("graphql".to_string());
+        Ok(res)
+    }
+
+    /// Download the subdivisions of all supported countries into the offline catalogue.
+    /// Countries whose download fails keep their stored subdivisions.
+    async fn catalogue_refresh(ctx: &Context) -> anyhow::Result<GQLCatalogueRefresh> {
+        ctx.require_role(Role::Admin)?;
+        // downloaded before the transaction is started, it would block all writes meanwhile
+        let download = crate::holidays::catalogue::download().await;
+        let stored =
+            match crate::holidays::catalogue::store_catalogue(ctx.txn().await?, download.catalogue)
+                .await
+            {
+                Ok(count) => count,
+                Err(err) => {
+                    ctx.failed().await;
+                    Err(err)?
+                }
+            };
+        Ok(GQLCatalogueRefresh { stored: stored as i32, failed_countries: download.failed })
+    }
+
+    /// Import a catalogue file (subdivisions as returned by the OpenHolidays API, keyed by
+    /// country isocode). Returns the number of stored subdivisions.
+    async fn catalogue_import(ctx: &Context, content: String) -> anyhow::Result<i32> {
+        ctx.require_role(Role::Admin)?;
+        let count = match crate::holidays::catalogue::import(ctx.txn().await?, &content).await {
+            Ok(count) => count,
+            Err(err) => {
+                ctx.failed().await;
+                Err(err)?
+            }
+        };
+        Ok(count as i32)
+    }
+
+    /// Create an empty holiday calendar whose entries are entered by hand, e.g. a company
+    /// calendar. Global calendars apply to all resources.
+    async fn holiday_create_manual(
+        ctx: &Context,
+        name: String,
+        global: Option<bool>,
+    ) -> anyhow::Result<GQLHoliday> {
+        ctx.require_role(Role::Admin)?;
+        let res = match holiday_create_manual(ctx, name, global.unwrap_or(false)).await {
+            Ok(res) => res,
+            Err(err) => {
+                ctx.failed().await;
+                Err(err)?
+            }
+        };
+        if global.unwrap_or(false) {
+            ctx.app_state().notify_modified("graphql".to_string());
+        }
+        Ok(res)
+    }
+
+    /// Apply a holiday calendar to all resources (or stop doing so)
+    async fn holiday_set_global(
+        ctx: &Context,
+        db_id: i32,
+        global: bool,
+    ) -> anyhow::Result<GQLHoliday> {
+        ctx.require_role(Role::Admin)?;
+        let txn = ctx.txn().await?;
+        let before = holiday_snapshot(txn, db_id).await?;
+        let am = holiday::ActiveModel {
+            id: ActiveValue::Set(db_id),
+            global: ActiveValue::Set(global),
+            ..Default::default()
+        };
+        let res = am.update(txn).await?;
+        record_holiday_change(ctx, db_id, before).await?;
+        ctx.app_state().notify_modified("graphql".to_string());
+        Ok(GQLHoliday::from_model(res))
+    }
+
+    /// Delete a holiday calendar including its entries. Resources using it lose the reference.
+    async fn holiday_delete(ctx: &Context, db_id: i32) -> anyhow::Result<bool> {
+        ctx.require_role(Role::Admin)?;
+        let txn = ctx.txn().await?;
+        // school_holiday_id has no foreign key (SQLite cannot add them to existing tables)
+        resource::Entity::update_many()
+            .col_expr(resource::Column::SchoolHolidayId, Expr::value(Option::<i32>::None))
+            .filter(resource::Column::SchoolHolidayId.eq(db_id))
+            .exec(txn)
+            .await?;
+        let before = holiday_snapshot(txn, db_id).await?;
+        record_change(ctx, AuditEntityType::Holiday, db_id, before.as_ref(), None).await?;
+        let am = holiday::ActiveModel { id: ActiveValue::Set(db_id), ..Default::default() };
+        let res = am.delete(txn).await?;
+        let ok = res.rows_affected > 0;
+        if ok {
+            ctx.app_state().notify_modified("graphql".to_string());
+        }
+        Ok(ok)
+    }
+
+    /// Add a day off to a manually maintained holiday calendar
+    async fn holiday_entry_add(
+        ctx: &Context,
+        holiday_id: i32,
+        date: chrono::NaiveDate,
+        name: Option<String>,
+        fraction: Option<f64>,
+        entry_type: Option<HolidayEntryType>,
+    ) -> anyhow::Result<holiday_entry::Model> {
+        ctx.require_role(Role::Admin)?;
+        let res = match holiday_entry_add(ctx, holiday_id, date, name, fraction, entry_type).await {
+            Ok(res) => res,
+            Err(err) => {
+                ctx.failed().await;
+                Err(err)?
+            }
+        };
+        ctx.app_state().notify_modified("graphql".to_string());
+        Ok(res)
+    }
+
+    /// Remove a day off from a manually maintained holiday calendar
+    async fn holiday_entry_delete(ctx: &Context, db_id: i32) -> anyhow::Result<bool> {
+        ctx.require_role(Role::Admin)?;
+        let ok = match holiday_entry_delete(ctx, db_id).await {
+            Ok(ok) => ok,
+            Err(err) => {
+                ctx.failed().await;
+                Err(err)?
+            }
+        };
+        if ok {
+            ctx.app_state().notify_modified("graphql".to_string());
+        }
+        Ok(ok)
+    }
+
+    /// Create or update a user account. Requires a login, the first account of an installation is
+    /// created with `siapla-serve --create-admin`.
+    async fn user_save(ctx: &Context, user: UserSaveInput) -> anyhow::Result<user::Model> {
+        match user_save(ctx, user).await {
+            Ok(res) => Ok(res),
+            Err(err) => {
+                ctx.failed().await;
+                Err(err)?
+            }
+        }
+    }
+
+    async fn user_delete(ctx: &Context, db_id: i32) -> anyhow::Result<bool> {
+        match user_delete(ctx, db_id).await {
+            Ok(res) => Ok(res),
+            Err(err) => {
+                ctx.failed().await;
+                Err(err)?
+            }
+        }
+    }
+
+    /// Create an API token for scripts acting as the logged in user
+    async fn api_token_create(
+        ctx: &Context,
+        name: String,
+        expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> anyhow::Result<GQLCreatedApiToken> {
+        match api_token_create(ctx, name, expires_at).await {
+            Ok(res) => Ok(res),
+            Err(err) => {
+                ctx.failed().await;
+                Err(err)?
+            }
+        }
+    }
+
+    async fn api_token_delete(ctx: &Context, db_id: i32) -> anyhow::Result<bool> {
+        match api_token_delete(ctx, db_id).await {
+            Ok(res) => Ok(res),
+            Err(err) => {
+                ctx.failed().await;
+                Err(err)?
+            }
+        }
+    }
+
+    /// Revert the last changes of the current user, e.g. a task dragged into the wrong group.
+    /// Returns the reverted change set, none if there is nothing to undo.
+    async fn undo(ctx: &Context) -> anyhow::Result<Option<change_set::Model>> {
+        let res = match undo(ctx).await {
+            Ok(res) => res,
+            Err(err) => {
+                ctx.failed().await;
+                Err(err)?
+            }
+        };
+        if res.is_some() {
+            ctx.app_state().notify_modified("graphql".to_string());
+        }
+        Ok(res)
+    }
+
+    /// Apply the last undone changes of the current user again. Returns the applied change set,
+    /// none if there is nothing to redo.
+    async fn redo(ctx: &Context) -> anyhow::Result<Option<change_set::Model>> {
+        let res = match redo(ctx).await {
+            Ok(res) => res,
+            Err(err) => {
+                ctx.failed().await;
+                Err(err)?
+            }
+        };
+        if res.is_some() {
+            ctx.app_state().notify_modified("graphql".to_string());
+        }
+        Ok(res)
+    }
+
     /// Trigger a manual recalculation now
     async fn recalculate_now(ctx: &Context) -> anyhow::Result<bool> {
+        ctx.require_role(Role::Planner)?;
         ctx.app_state().trigger_manual();
         Ok(true)
     }
+
+    /// Cancel the running recalculation, the last plan is kept. Returns false if no
+    /// calculation is running.
+    async fn cancel_recalculation(ctx: &Context) -> anyhow::Result<bool> {
+        ctx.require_role(Role::Planner)?;
+        Ok(ctx.app_state().cancel_calculation())
+    }
+}
+
+/// Planners may book for everyone, members only for their own resource (`resources` are the new
+/// resources of the booking `db_id`, none when it is deleted). Members cannot create or change
+/// bookings without resources.
+async fn check_booking_access(
+    ctx: &Context,
+    db_id: Option<i32>,
+    resources: Option<&[i32]>,
+) -> anyhow::Result<()> {
+    if ctx.role() >= Role::Planner {
+        return Ok(());
+    }
+    ctx.require_role(Role::Member)?;
+    let own = ctx
+        .user()
+        .and_then(|user| user.resource_id)
+        .ok_or(anyhow::anyhow!("Permission denied, your account has no resource"))?;
+    if resources.is_some_and(|resources| resources.is_empty()) {
+        return Err(anyhow::anyhow!("Permission denied, members can only book for themselves"));
+    }
+    let mut booked = resources.unwrap_or_default().to_vec();
+    if let Some(db_id) = db_id {
+        let existing = allocated_resource::Entity::find()
+            .filter(allocated_resource::Column::AllocationId.eq(db_id))
+            .all(ctx.txn().await?)
+            .await?;
+        if existing.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Permission denied, members can only change their own bookings"
+            ));
+        }
+        booked.extend(existing.into_iter().map(|ar| ar.resource_id));
+    }
+    if booked.is_empty() || booked.iter().any(|rid| *rid != own) {
+        return Err(anyhow::anyhow!("Permission denied, members can only book for themselves"));
+    }
+    Ok(())
 }
@@ -1,9 +1,14 @@
 use crate::{
-    entity::{holiday, issue, resource, task},
+    auth::Role,
+    entity::{
+        audit_log, auth_token, calculation_run, change_set, holiday, issue, milestone_contention,
+        project, resource, task, team, user,
+    },
     gql::plan::Plan,
 };
 
 use super::{
+    audit_log::AuditEntityType,
     context::Context,
     holiday::{Country, GQLHoliday, Region},
 };
@@ -25,9 +30,36 @@ impl Query {
         Ok("Hello World from Juniper!".to_owned())
     }
 
-    async fn tasks(ctx: &Context) -> anyhow::Result<Vec<task::Model>> {
-        let res =
-            task::Entity::find().order_by_asc(task::Column::Title).all(ctx.txn().await?).await?;
+    /// All tasks, or only the tasks of one project
+    async fn tasks(ctx: &Context, project_id: Option<i32>) -> anyhow::Result<Vec<task::Model>> {
+        let mut query = task::Entity::find();
+        if let Some(project_id) = project_id {
+            query = query.filter(task::Column::ProjectId.eq(project_id));
+        }
+        let res = query.order_by_asc(task::Column::Title).all(ctx.txn().await?).await?;
+        Ok(res)
+    }
+
+    async fn projects(ctx: &Context) -> anyhow::Result<Vec<project::Model>> {
+        let res = project::Entity::find()
+            .order_by_asc(project::Column::Name)
+            .all(ctx.txn().await?)
+            .await?;
+        Ok(res)
+    }
+
+    async fn project(ctx: &Context, db_id: i32) -> anyhow::Result<Option<project::Model>> {
+        let res = project::Entity::find_by_id(db_id).one(ctx.txn().await?).await?;
+        Ok(res)
+    }
+
+    /// Portfolio view: milestones of all projects delayed by another project in the current plan
+    async fn portfolio(ctx: &Context) -> anyhow::Result<Vec<milestone_contention::Model>> {
+        let res = milestone_contention::Entity::find()
+            .order_by_asc(milestone_contention::Column::MilestoneId)
+            .order_by_asc(milestone_contention::Column::ProjectId)
+            .all(ctx.txn().await?)
+            .await?;
         Ok(res)
     }
 
@@ -39,6 +71,75 @@ impl Query {
         Ok(res)
     }
 
+    async fn teams(ctx: &Context) -> anyhow::Result<Vec<team::Model>> {
+        let res =
+            team::Entity::find().order_by_asc(team::Column::Name).all(ctx.txn().await?).await?;
+        Ok(res)
+    }
+
+    /// The logged in user, none while no user accounts exist
+    async fn me(ctx: &Context) -> Option<user::Model> {
+        ctx.user().cloned()
+    }
+
+    async fn users(ctx: &Context) -> anyhow::Result<Vec<user::Model>> {
+        let res =
+            user::Entity::find().order_by_asc(user::Column::Name).all(ctx.txn().await?).await?;
+        Ok(res)
+    }
+
+    /// API tokens of the logged in user
+    async fn api_tokens(ctx: &Context) -> anyhow::Result<Vec<auth_token::Model>> {
+        super::user::api_tokens(ctx).await
+    }
+
+    /// The changes of the current user that `undo` would revert
+    async fn undoable(ctx: &Context) -> anyhow::Result<Option<change_set::Model>> {
+        super::change_set::undoable(ctx).await
+    }
+
+    /// The changes of the current user that `redo` would apply again
+    async fn redoable(ctx: &Context) -> anyhow::Result<Option<change_set::Model>> {
+        super::change_set::redoable(ctx).await
+    }
+
+    /// Changes of an entity, newest first. Entries contain private data: resources are visible
+    /// to planners and the person using the resource, users to admins and everything else to
+    /// planners.
+    async fn history(
+        ctx: &Context,
+        entity_type: AuditEntityType,
+        id: i32,
+    ) -> anyhow::Result<Vec<audit_log::Model>> {
+        match entity_type {
+            AuditEntityType::Resource if ctx.may_view_private(id) => {}
+            AuditEntityType::User => ctx.require_role(Role::Admin)?,
+            _ => ctx.require_role(Role::Planner)?,
+        }
+        let res = audit_log::Entity::find()
+            .filter(audit_log::Column::EntityType.eq(<&'static str>::from(entity_type)))
+            .filter(audit_log::Column::EntityId.eq(id))
+            .order_by_desc(audit_log::Column::ChangedAt)
+            .order_by_desc(audit_log::Column::Id)
+            .all(ctx.txn().await?)
+            .await?;
+        Ok(res)
+    }
+
+    /// Runs of the scheduler, newest first
+    async fn calculation_runs(
+        ctx: &Context,
+        limit: Option<i32>,
+    ) -> anyhow::Result<Vec<calculation_run::Model>> {
+        let res = calculation_run::Entity::find()
+            .order_by_desc(calculation_run::Column::StartedAt)
+            .order_by_desc(calculation_run::Column::Id)
+            .limit(limit.map(|limit| limit.max(0) as u64))
+            .all(ctx.txn().await?)
+            .await?;
+        Ok(res)
+    }
+
     async fn countries() -> Vec<Country> {
         super::holiday::countries()
             .iter()
@@ -73,6 +174,20 @@ impl Query {
         Ok(Some(GQLHoliday::from_model(result)))
     }
 
+    /// All holiday calendars known to the database
+    async fn holidays(ctx: &Context) -> anyhow::Result<Vec<GQLHoliday>> {
+        let res = holiday::Entity::find()
+            .order_by_asc(holiday::Column::Name)
+            .all(ctx.txn().await?)
+            .await?;
+        Ok(res.into_iter().map(GQLHoliday::from_model).collect())
+    }
+
+    /// The offline subdivision catalogue, in the format accepted by `catalogueImport`
+    async fn catalogue_export(ctx: &Context) -> anyhow::Result<String> {
+        crate::holidays::catalogue::export(ctx.txn().await?).await
+    }
+
     async fn current_plan(_ctx: &Context) -> Plan {
         Plan {}
     }
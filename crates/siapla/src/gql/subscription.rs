@@ -1,3 +1,6 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
 use futures::StreamExt;
 use futures::stream::BoxStream;
 use juniper::GraphQLEnum;
@@ -5,10 +8,13 @@ use juniper::graphql_object;
 use juniper::graphql_subscription;
 use strum::EnumString;
 use strum::IntoStaticStr;
-use tokio_stream::wrappers::WatchStream;
+use tokio_stream::wrappers::{BroadcastStream, WatchStream};
 
-use super::context::Context;
-use crate::app_state::CalculationState;
+use super::{
+    audit_log::{AuditAction, AuditEntityType},
+    context::Context,
+};
+use crate::{app_state::CalculationState, entity::audit_log, scheduling::GAProgress};
 
 #[derive(Clone, Copy, Default)]
 pub struct Subscription {}
@@ -30,11 +36,19 @@ impl GQLCalculationUpdate {
     pub fn state(&self) -> GQLCalculationState {
         match &self.inner {
             CalculationState::Modified => GQLCalculationState::Modified,
-            CalculationState::Calculating => GQLCalculationState::Calculating,
+            CalculationState::Calculating(_) => GQLCalculationState::Calculating,
             CalculationState::Finished => GQLCalculationState::Finished,
         }
     }
 
+    /// Progress of the running calculation, none while loading the problem
+    pub fn progress(&self) -> Option<&GAProgress> {
+        match &self.inner {
+            CalculationState::Calculating(progress) => progress.as_ref(),
+            _ => None,
+        }
+    }
+
     pub async fn plan(&self, _ctx: &Context) -> Option<crate::gql::types::plan::Plan> {
         match &self.inner {
             CalculationState::Finished => Some(crate::gql::types::plan::Plan {}),
@@ -43,6 +57,50 @@ impl GQLCalculationUpdate {
     }
 }
 
+/// A committed change of an entity. The changed values are not part of the event, they are
+/// fetched through the queries (`history` for the audit log entry) which check the permissions.
+pub struct GQLEntityChange {
+    inner: audit_log::Model,
+}
+
+#[graphql_object(name = "EntityChange")]
+impl GQLEntityChange {
+    pub fn entity_type(&self) -> anyhow::Result<AuditEntityType> {
+        Ok(AuditEntityType::from_str(&self.inner.entity_type)?)
+    }
+    pub fn entity_id(&self) -> i32 {
+        self.inner.entity_id
+    }
+    pub fn action(&self) -> anyhow::Result<AuditAction> {
+        Ok(AuditAction::from_str(&self.inner.action)?)
+    }
+    pub fn changed_at(&self) -> &DateTime<Utc> {
+        &self.inner.changed_at
+    }
+}
+
+#[graphql_object(name = "CalculationProgress")]
+impl GAProgress {
+    /// Number of finished iterations of the genetic algorithm
+    pub fn iteration(&self) -> i32 {
+        self.iteration as i32
+    }
+    pub fn iterations(&self) -> i32 {
+        self.iterations as i32
+    }
+    /// Cost of the best plan found so far, lower is better
+    pub fn best_cost(&self) -> f64 {
+        self.best_cost
+    }
+    pub fn elapsed_seconds(&self) -> f64 {
+        self.elapsed.as_secs_f64()
+    }
+    /// Estimated remaining time, none before the first iteration is finished
+    pub fn remaining_seconds(&self) -> Option<f64> {
+        self.remaining().map(|remaining| remaining.as_secs_f64())
+    }
+}
+
 #[graphql_subscription]
 #[graphql(context = Context)]
 impl Subscription {
@@ -58,6 +116,66 @@ impl Subscription {
         let stream = WatchStream::new(rx).map(|s| Ok(GQLCalculationUpdate { inner: s }));
         Box::pin(stream)
     }
+
+    /// Committed changes of entities, optionally only of some entity types
+    async fn entity_changed(
+        ctx: &Context,
+        entity_types: Option<Vec<AuditEntityType>>,
+    ) -> BoxStream<'static, Result<GQLEntityChange, juniper::FieldError>> {
+        changes(ctx, entity_types, None)
+    }
+
+    /// Committed changes of tasks, optionally only of the given tasks
+    async fn task_changed(
+        ctx: &Context,
+        ids: Option<Vec<i32>>,
+    ) -> BoxStream<'static, Result<GQLEntityChange, juniper::FieldError>> {
+        changes(ctx, Some(vec![AuditEntityType::Task]), ids)
+    }
+
+    /// Committed changes of resources, optionally only of the given resources
+    async fn resource_changed(
+        ctx: &Context,
+        ids: Option<Vec<i32>>,
+    ) -> BoxStream<'static, Result<GQLEntityChange, juniper::FieldError>> {
+        changes(ctx, Some(vec![AuditEntityType::Resource]), ids)
+    }
+
+    /// Committed changes of bookings, optionally only of the given bookings
+    async fn booking_changed(
+        ctx: &Context,
+        ids: Option<Vec<i32>>,
+    ) -> BoxStream<'static, Result<GQLEntityChange, juniper::FieldError>> {
+        changes(ctx, Some(vec![AuditEntityType::Booking]), ids)
+    }
+
+    /// Committed changes of projects, optionally only of the given projects
+    async fn project_changed(
+        ctx: &Context,
+        ids: Option<Vec<i32>>,
+    ) -> BoxStream<'static, Result<GQLEntityChange, juniper::FieldError>> {
+        changes(ctx, Some(vec![AuditEntityType::Project]), ids)
+    }
+}
+
+/// Stream of committed changes, filtered by entity type and id. Changes missed by slow
+/// subscribers are skipped.
+fn changes(
+    ctx: &Context,
+    entity_types: Option<Vec<AuditEntityType>>,
+    ids: Option<Vec<i32>>,
+) -> BoxStream<'static, Result<GQLEntityChange, juniper::FieldError>> {
+    let entity_types: Option<Vec<&'static str>> =
+        entity_types.map(|types| types.into_iter().map(Into::into).collect());
+    let rx = ctx.app_state().change_tx.subscribe();
+    let stream = BroadcastStream::new(rx).filter_map(move |change| {
+        let change = change.ok().filter(|change| {
+            entity_types.as_ref().is_none_or(|types| types.contains(&change.entity_type.as_str()))
+                && ids.as_ref().is_none_or(|ids| ids.contains(&change.entity_id))
+        });
+        futures::future::ready(change.map(|inner| Ok(GQLEntityChange { inner })))
+    });
+    Box::pin(stream)
 }
 
 impl Subscription {
@@ -51,6 +51,10 @@ impl allocation::Model {
             resource::Column::Id
         )
     }
+    /// Whether any of the allocated resources is a placeholder for a planned hire
+    pub async fn depends_on_hire(&self, ctx: &Context) -> anyhow::Result<bool> {
+        Ok(self.resources(ctx).await?.iter().any(|r| r.placeholder))
+    }
     pub async fn task(&self, ctx: &Context) -> anyhow::Result<task::Model> {
         const CIDX: usize = task::Column::Id as usize;
         ctx.load_one_by_col::<task::Entity, CIDX>(self.task_id)
@@ -0,0 +1,401 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use juniper::{GraphQLEnum, graphql_object};
+use sea_orm::prelude::*;
+use sea_orm::{ActiveValue, DatabaseTransaction, QueryOrder};
+use serde::Serialize;
+use serde_json::Value;
+use strum::{EnumString, IntoStaticStr};
+
+use crate::{
+    entity::{
+        allocated_resource, allocation, audit_log, availability, availability_exception,
+        availability_pattern, blocked_time, holiday, holiday_entry, ramp_up_stage, resource,
+        resource_focus, resource_holiday, team, team_membership, user, vacation,
+    },
+    gql::context::Context,
+};
+
+/// Secrets and bookkeeping fields are not written to the audit log
+const IGNORED_FIELDS: [&str; 3] = ["password_hash", "modified_at", "calculated_at"];
+
+#[derive(GraphQLEnum, IntoStaticStr, EnumString, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum AuditEntityType {
+    Task,
+    Resource,
+    Booking,
+    Project,
+    Team,
+    User,
+    Holiday,
+    Dependency,
+    ResourceConstraint,
+    ResourceConstraintEntry,
+}
+
+impl AuditEntityType {
+    /// Whether changes can be undone. Other entities have nested data that is not part of the
+    /// audit log (e.g. vacations of resources), their changes are only logged.
+    pub fn undoable(&self) -> bool {
+        match self {
+            AuditEntityType::Task
+            | AuditEntityType::Booking
+            | AuditEntityType::Project
+            | AuditEntityType::Dependency
+            | AuditEntityType::ResourceConstraint
+            | AuditEntityType::ResourceConstraintEntry => true,
+            AuditEntityType::Resource
+            | AuditEntityType::Team
+            | AuditEntityType::User
+            | AuditEntityType::Holiday => false,
+        }
+    }
+}
+
+impl From<AuditEntityType> for String {
+    fn from(value: AuditEntityType) -> Self {
+        let s: &'static str = value.into();
+        s.into()
+    }
+}
+
+#[derive(GraphQLEnum, IntoStaticStr, EnumString, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+}
+
+impl From<AuditAction> for String {
+    fn from(value: AuditAction) -> Self {
+        let s: &'static str = value.into();
+        s.into()
+    }
+}
+
+pub fn audit_value(value: &impl Serialize) -> anyhow::Result<Value> {
+    let mut value = serde_json::to_value(value)?;
+    if let Value::Object(map) = &mut value {
+        for field in IGNORED_FIELDS {
+            map.remove(field);
+        }
+    }
+    Ok(value)
+}
+
+/// Record the change of an entity in the transaction of the request, so it is only kept if the
+/// change is committed. Without `before` the entity was created, without `after` deleted.
+/// Undoable changes become part of the change set of the request.
+pub async fn record_change<M: Serialize>(
+    ctx: &Context,
+    entity_type: AuditEntityType,
+    entity_id: i32,
+    before: Option<&M>,
+    after: Option<&M>,
+) -> anyhow::Result<()> {
+    let before = before.map(audit_value).transpose()?;
+    let after = after.map(audit_value).transpose()?;
+    if before == after {
+        return Ok(());
+    }
+    let change_set_id = if entity_type.undoable() { Some(ctx.change_set().await?) } else { None };
+    insert_entry(ctx, change_set_id, entity_type, entity_id, before, after).await
+}
+
+/// Record the deletion of several entities, e.g. rows removed by a cascade
+pub async fn record_deletes<M: Serialize>(
+    ctx: &Context,
+    entity_type: AuditEntityType,
+    models: &[M],
+    id: fn(&M) -> i32,
+) -> anyhow::Result<()> {
+    for model in models {
+        record_change(ctx, entity_type, id(model), Some(model), None).await?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn insert_entry(
+    ctx: &Context,
+    change_set_id: Option<i32>,
+    entity_type: AuditEntityType,
+    entity_id: i32,
+    before: Option<Value>,
+    after: Option<Value>,
+) -> anyhow::Result<()> {
+    let action = match (&before, &after) {
+        (None, None) => return Ok(()),
+        (Some(before), Some(after)) if before == after => return Ok(()),
+        (None, Some(_)) => AuditAction::Create,
+        (Some(_), None) => AuditAction::Delete,
+        (Some(_), Some(_)) => AuditAction::Update,
+    };
+    let am = audit_log::ActiveModel {
+        id: ActiveValue::NotSet,
+        user_id: ActiveValue::Set(ctx.user().map(|u| u.id)),
+        user_name: ActiveValue::Set(ctx.user().map(|u| u.name.clone())),
+        entity_type: ActiveValue::Set(entity_type.into()),
+        entity_id: ActiveValue::Set(entity_id),
+        action: ActiveValue::Set(action.into()),
+        before: ActiveValue::Set(before.map(|v| v.to_string())),
+        after: ActiveValue::Set(after.map(|v| v.to_string())),
+        changed_at: ActiveValue::Set(Utc::now()),
+        change_set_id: ActiveValue::Set(change_set_id),
+    };
+    ctx.add_change(am.insert(ctx.txn().await?).await?);
+    Ok(())
+}
+
+/// A single changed value of an audit log entry, values are JSON
+pub struct GQLFieldChange {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[graphql_object]
+#[graphql(name = "FieldChange")]
+impl GQLFieldChange {
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+    pub fn before(&self) -> &Option<String> {
+        &self.before
+    }
+    pub fn after(&self) -> &Option<String> {
+        &self.after
+    }
+}
+
+#[graphql_object]
+#[graphql(name = "AuditEntry")]
+impl audit_log::Model {
+    fn db_id(&self) -> &i32 {
+        &self.id
+    }
+    fn entity_type(&self) -> anyhow::Result<AuditEntityType> {
+        Ok(AuditEntityType::from_str(&self.entity_type)?)
+    }
+    fn entity_id(&self) -> &i32 {
+        &self.entity_id
+    }
+    fn action(&self) -> anyhow::Result<AuditAction> {
+        Ok(AuditAction::from_str(&self.action)?)
+    }
+    /// The user who made the change, none for deleted users and changes without login
+    async fn user(&self, ctx: &Context) -> anyhow::Result<Option<user::Model>> {
+        const CIDX: usize = user::Column::Id as usize;
+        ctx.load_one_by_col::<user::Entity, CIDX>(self.user_id).await
+    }
+    /// Name of the user at the time of the change
+    fn user_name(&self) -> &Option<String> {
+        &self.user_name
+    }
+    fn changed_at(&self) -> &DateTime<Utc> {
+        &self.changed_at
+    }
+    /// The entity before the change as JSON
+    fn before(&self) -> &Option<String> {
+        &self.before
+    }
+    /// The entity after the change as JSON
+    fn after(&self) -> &Option<String> {
+        &self.after
+    }
+    /// Fields whose values differ between `before` and `after`
+    fn changes(&self) -> anyhow::Result<Vec<GQLFieldChange>> {
+        let parse = |json: &Option<String>| -> anyhow::Result<serde_json::Map<String, Value>> {
+            match json.as_deref().map(serde_json::from_str::<Value>).transpose()? {
+                None => Ok(Default::default()),
+                Some(Value::Object(map)) => Ok(map),
+                Some(_) => Err(anyhow!("Audit log entry {} is not an object", self.id)),
+            }
+        };
+        let before = parse(&self.before)?;
+        let after = parse(&self.after)?;
+        let mut fields: Vec<&String> = before.keys().chain(after.keys()).collect();
+        fields.sort();
+        fields.dedup();
+        Ok(fields
+            .into_iter()
+            .filter(|field| before.get(*field) != after.get(*field))
+            .map(|field| GQLFieldChange {
+                field: field.clone(),
+                before: before.get(field).map(Value::to_string),
+                after: after.get(field).map(Value::to_string),
+            })
+            .collect())
+    }
+}
+
+/// A booking with its resources, as recorded in the audit log
+pub async fn booking_snapshot(
+    txn: &DatabaseTransaction,
+    db_id: i32,
+) -> anyhow::Result<Option<Value>> {
+    let Some(booking) = allocation::Entity::find_by_id(db_id).one(txn).await? else {
+        return Ok(None);
+    };
+    let mut resources: Vec<i32> = allocated_resource::Entity::find()
+        .filter(allocated_resource::Column::AllocationId.eq(db_id))
+        .all(txn)
+        .await?
+        .into_iter()
+        .map(|ar| ar.resource_id)
+        .collect();
+    resources.sort();
+    let mut value = audit_value(&booking)?;
+    value["resources"] = resources.into();
+    Ok(Some(value))
+}
+
+/// Rows of an entity's nested data, ordered by id and without the reference to the parent
+/// (`parent_col`, serialized as `parent_field`)
+async fn child_rows<E>(
+    txn: &DatabaseTransaction,
+    parent_col: E::Column,
+    parent_field: &str,
+    id_col: E::Column,
+    parent_id: i32,
+) -> anyhow::Result<Value>
+where
+    E: EntityTrait,
+    E::Model: Serialize,
+{
+    let mut rows = vec![];
+    for model in E::find().filter(parent_col.eq(parent_id)).order_by_asc(id_col).all(txn).await? {
+        let mut value = audit_value(&model)?;
+        if let Value::Object(map) = &mut value {
+            map.remove(parent_field);
+        }
+        rows.push(value);
+    }
+    Ok(rows.into())
+}
+
+/// A resource with its vacations, availability and other nested data, as recorded in the audit
+/// log
+pub async fn resource_snapshot(
+    txn: &DatabaseTransaction,
+    db_id: i32,
+) -> anyhow::Result<Option<Value>> {
+    let Some(resource) = resource::Entity::find_by_id(db_id).one(txn).await? else {
+        return Ok(None);
+    };
+    let mut value = audit_value(&resource)?;
+    value["vacations"] = child_rows::<vacation::Entity>(
+        txn,
+        vacation::Column::ResourceId,
+        "resource_id",
+        vacation::Column::Id,
+        db_id,
+    )
+    .await?;
+    value["availability"] = child_rows::<availability::Entity>(
+        txn,
+        availability::Column::ResourceId,
+        "resource_id",
+        availability::Column::Id,
+        db_id,
+    )
+    .await?;
+    value["availability_patterns"] = child_rows::<availability_pattern::Entity>(
+        txn,
+        availability_pattern::Column::ResourceId,
+        "resource_id",
+        availability_pattern::Column::Id,
+        db_id,
+    )
+    .await?;
+    value["availability_exceptions"] = child_rows::<availability_exception::Entity>(
+        txn,
+        availability_exception::Column::ResourceId,
+        "resource_id",
+        availability_exception::Column::Id,
+        db_id,
+    )
+    .await?;
+    value["blocked_times"] = child_rows::<blocked_time::Entity>(
+        txn,
+        blocked_time::Column::ResourceId,
+        "resource_id",
+        blocked_time::Column::Id,
+        db_id,
+    )
+    .await?;
+    value["focus"] = child_rows::<resource_focus::Entity>(
+        txn,
+        resource_focus::Column::ResourceId,
+        "resource_id",
+        resource_focus::Column::Id,
+        db_id,
+    )
+    .await?;
+    value["ramp_up"] = child_rows::<ramp_up_stage::Entity>(
+        txn,
+        ramp_up_stage::Column::ResourceId,
+        "resource_id",
+        ramp_up_stage::Column::Id,
+        db_id,
+    )
+    .await?;
+    let mut holidays: Vec<i32> = resource_holiday::Entity::find()
+        .filter(resource_holiday::Column::ResourceId.eq(db_id))
+        .all(txn)
+        .await?
+        .into_iter()
+        .map(|rh| rh.holiday_id)
+        .collect();
+    holidays.sort();
+    value["additional_holidays"] = holidays.into();
+    Ok(Some(value))
+}
+
+/// A holiday calendar with its entries, as recorded in the audit log. Entries are identified by
+/// date and type, their ids change when the calendar is refreshed or imported again.
+pub async fn holiday_snapshot(
+    txn: &DatabaseTransaction,
+    db_id: i32,
+) -> anyhow::Result<Option<Value>> {
+    let Some(holiday) = holiday::Entity::find_by_id(db_id).one(txn).await? else {
+        return Ok(None);
+    };
+    let mut entries = vec![];
+    for entry in holiday_entry::Entity::find()
+        .filter(holiday_entry::Column::HolidayId.eq(db_id))
+        .order_by_asc(holiday_entry::Column::Date)
+        .order_by_asc(holiday_entry::Column::Type)
+        .order_by_asc(holiday_entry::Column::Id)
+        .all(txn)
+        .await?
+    {
+        let mut value = audit_value(&entry)?;
+        if let Value::Object(map) = &mut value {
+            map.remove("id");
+            map.remove("holiday_id");
+        }
+        entries.push(value);
+    }
+    let mut value = audit_value(&holiday)?;
+    value["entries"] = entries.into();
+    Ok(Some(value))
+}
+
+/// A team with its membership history, as recorded in the audit log
+pub async fn team_snapshot(txn: &DatabaseTransaction, db_id: i32) -> anyhow::Result<Option<Value>> {
+    let Some(team) = team::Entity::find_by_id(db_id).one(txn).await? else {
+        return Ok(None);
+    };
+    let mut value = audit_value(&team)?;
+    value["memberships"] = child_rows::<team_membership::Entity>(
+        txn,
+        team_membership::Column::TeamId,
+        "team_id",
+        team_membership::Column::Id,
+        db_id,
+    )
+    .await?;
+    Ok(Some(value))
+}
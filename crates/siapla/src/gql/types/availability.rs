@@ -1,10 +1,11 @@
-use std::{collections::HashSet, iter::zip};
+use std::collections::HashSet;
 
 use crate::{
-    entity::{availability, resource},
-    gql::context::Context,
+    entity::{availability, availability_pattern, resource},
+    gql::{common::opt_to_av, context::Context},
 };
 use anyhow::anyhow;
+use chrono::{NaiveDate, NaiveTime, TimeDelta};
 use juniper::{GraphQLEnum, graphql_object};
 use sea_orm::{ActiveValue, prelude::*};
 use strum::{EnumString, IntoStaticStr};
@@ -41,115 +42,244 @@ impl availability::Model {
             .await?;
         resource.ok_or(anyhow!("Failed to find resource for Availability"))
     }
-    fn duration(&self) -> anyhow::Result<i32> {
-        let mut secs = self.duration * Decimal::new(3600, 0);
-        secs.rescale(0); // rounding to whole seconds
-        Ok(secs.try_into()?)
+    /// Length of this block in seconds
+    fn duration(&self) -> i32 {
+        block_duration(self.start, self.end).num_seconds() as i32
     }
     fn weekday(&self) -> anyhow::Result<Weekday> {
         Ok(self.weekday.as_str().try_into()?)
     }
+    /// Local start time of this block (in the resource's timezone)
+    fn start(&self) -> NaiveTime {
+        self.start
+    }
+    /// Local end time of this block (in the resource's timezone), 00:00 denotes the end of the day
+    fn end(&self) -> NaiveTime {
+        self.end
+    }
+}
+
+#[graphql_object]
+#[graphql(name = "AvailabilityPattern")]
+impl availability_pattern::Model {
+    fn db_id(&self) -> &i32 {
+        &self.id
+    }
+    /// First day this pattern applies to (in the resource's timezone)
+    fn valid_from(&self) -> &Option<NaiveDate> {
+        &self.valid_from
+    }
+    /// Last day this pattern applies to (in the resource's timezone)
+    fn valid_until(&self) -> &Option<NaiveDate> {
+        &self.valid_until
+    }
+    async fn availability(&self, ctx: &Context) -> anyhow::Result<Vec<availability::Model>> {
+        const CIDX: usize = availability::Column::PatternId as usize;
+        ctx.load_by_col::<availability::Entity, CIDX>(self.id).await
+    }
+}
+
+/// Length of an availability block. An end time of 00:00 denotes the end of the day.
+pub fn block_duration(start: NaiveTime, end: NaiveTime) -> TimeDelta {
+    let duration = end - start;
+    if duration <= TimeDelta::zero() { duration + TimeDelta::days(1) } else { duration }
+}
+
+/// Block of the given length centered around noon, used for duration-only input. Returns `None`
+/// for a zero duration, as 12:00 - 12:00 would be read as a full day.
+pub fn noon_centered_block(duration_secs: i32) -> Option<(NaiveTime, NaiveTime)> {
+    if duration_secs <= 0 {
+        return None;
+    }
+    let half = (duration_secs / 2).min(12 * 3600) as u32;
+    let start = NaiveTime::from_num_seconds_from_midnight_opt(12 * 3600 - half, 0)
+        .expect("Must be a valid time");
+    let end = NaiveTime::from_num_seconds_from_midnight_opt((12 * 3600 + half) % (24 * 3600), 0)
+        .expect("Must be a valid time");
+    Some((start, end))
 }
 
+/// A block of working time on a weekday. Either `start` and `end` or (deprecated) only
+/// `duration` in seconds must be given. Several blocks per weekday are allowed.
 #[derive(juniper::GraphQLInputObject, Debug)]
 pub struct AvailabilityInput {
     weekday: Weekday,
-    duration: i32,
+    duration: Option<i32>,
+    start: Option<NaiveTime>,
+    end: Option<NaiveTime>,
 }
 
-impl From<&AvailabilityInput> for crate::entity::availability::ActiveModel {
-    fn from(value: &AvailabilityInput) -> Self {
-        crate::entity::availability::ActiveModel {
-            id: ActiveValue::NotSet,
-            resource_id: ActiveValue::NotSet,
-            weekday: ActiveValue::Set(value.weekday.into()),
-            duration: ActiveValue::Set(Decimal::from(value.duration) / Decimal::from(3600)),
+impl AvailabilityInput {
+    /// Default availability of placeholder resources: Monday to Friday, 09:00 - 17:00
+    pub fn placeholder_template() -> Vec<AvailabilityInput> {
+        [Weekday::Monday, Weekday::Tuesday, Weekday::Wednesday, Weekday::Thursday, Weekday::Friday]
+            .into_iter()
+            .map(|weekday| AvailabilityInput {
+                weekday,
+                duration: None,
+                start: NaiveTime::from_hms_opt(9, 0, 0),
+                end: NaiveTime::from_hms_opt(17, 0, 0),
+            })
+            .collect()
+    }
+
+    /// Start and end of this block, `None` if the block is empty. 00:00 - 00:00 is a full day.
+    fn block(&self) -> anyhow::Result<Option<(NaiveTime, NaiveTime)>> {
+        match (self.start, self.end, self.duration) {
+            (Some(start), Some(end), _) => {
+                if start == end && start != NaiveTime::MIN {
+                    return Err(anyhow!(
+                        "Availability on {:?} must not start and end at the same time ({}), \
+                         use 00:00 - 00:00 for a full day.",
+                        self.weekday,
+                        start
+                    ));
+                }
+                if end < start && end != NaiveTime::MIN {
+                    return Err(anyhow!(
+                        "Availability on {:?} must end after it starts ({} - {}).",
+                        self.weekday,
+                        start,
+                        end
+                    ));
+                }
+                Ok(Some((start, end)))
+            }
+            (None, None, Some(duration)) => Ok(noon_centered_block(duration)),
+            _ => Err(anyhow!(
+                "Availability on {:?} needs either start and end or a duration.",
+                self.weekday
+            )),
         }
     }
 }
 
+/// Availability valid for a range of days, overriding the default availability. If patterns
+/// overlap, the one starting last is used.
+#[derive(juniper::GraphQLInputObject, Debug)]
+pub struct AvailabilityPatternInput {
+    db_id: Option<i32>,
+    valid_from: Option<NaiveDate>,
+    valid_until: Option<NaiveDate>,
+    availability: Vec<AvailabilityInput>,
+}
+
+/// Replace the date ranged availability patterns of a resource. Patterns not contained in
+/// `patterns` are removed.
+pub async fn update_availability_patterns(
+    ctx: &Context,
+    model: &resource::Model,
+    patterns: Vec<AvailabilityPatternInput>,
+) -> anyhow::Result<()> {
+    let txn = ctx.txn().await?;
+    let existing: Vec<i32> = availability_pattern::Entity::find()
+        .filter(availability_pattern::Column::ResourceId.eq(model.id))
+        .all(txn)
+        .await?
+        .into_iter()
+        .map(|p| p.id)
+        .collect();
+    let remove: Vec<i32> = existing
+        .iter()
+        .filter(|id| !patterns.iter().any(|p| p.db_id == Some(**id)))
+        .cloned()
+        .collect();
+    if !remove.is_empty() {
+        availability::Entity::delete_many()
+            .filter(availability::Column::PatternId.is_in(remove.clone()))
+            .exec(txn)
+            .await?;
+        availability_pattern::Entity::delete_many()
+            .filter(availability_pattern::Column::Id.is_in(remove))
+            .exec(txn)
+            .await?;
+    }
+    for pattern in patterns {
+        if let (Some(from), Some(until)) = (pattern.valid_from, pattern.valid_until)
+            && until < from
+        {
+            return Err(anyhow!(
+                "Availability pattern must end after it starts ({} - {}).",
+                from,
+                until
+            ));
+        }
+        if let Some(id) = pattern.db_id
+            && !existing.contains(&id)
+        {
+            return Err(anyhow!("Availability pattern {} does not belong to this resource.", id));
+        }
+        let am = availability_pattern::ActiveModel {
+            id: opt_to_av!(pattern.db_id),
+            resource_id: ActiveValue::Set(model.id),
+            valid_from: ActiveValue::Set(pattern.valid_from),
+            valid_until: ActiveValue::Set(pattern.valid_until),
+        };
+        let pattern_model =
+            if am.id.is_set() { am.update(txn).await? } else { am.insert(txn).await? };
+        update_availability(ctx, model, Some(pattern_model.id), pattern.availability).await?;
+    }
+    Ok(())
+}
+
+/// Replace the availability blocks of the default pattern (`pattern_id` None) or of the given
+/// pattern of a resource.
 pub async fn update_availability(
     ctx: &Context,
     model: &resource::Model,
+    pattern_id: Option<i32>,
     availability: Vec<AvailabilityInput>,
 ) -> anyhow::Result<()> {
     let txn = ctx.txn().await?;
-    let existing_availability: Vec<_> = model.availability(ctx).await?.into_iter().collect();
-    let existing: HashSet<Weekday> = existing_availability
+    let existing_availability: Vec<_> = availability::Entity::find()
+        .filter(availability::Column::ResourceId.eq(model.id))
+        .filter(match pattern_id {
+            Some(pattern_id) => availability::Column::PatternId.eq(pattern_id),
+            None => availability::Column::PatternId.is_null(),
+        })
+        .all(txn)
+        .await?;
+    let mut target: HashSet<(String, NaiveTime, NaiveTime)> = HashSet::new();
+    for a in &availability {
+        if let Some((start, end)) = a.block()? {
+            target.insert((a.weekday.into(), start, end));
+        }
+    }
+    let existing: HashSet<(String, NaiveTime, NaiveTime)> = existing_availability
+        .iter()
+        .map(|a| (a.weekday.clone(), a.start, a.end))
+        .collect();
+    let remove: Vec<i32> = existing_availability
         .iter()
-        .map(|el| el.weekday())
-        .collect::<anyhow::Result<_>>()?;
-    let target: HashSet<Weekday> = availability.iter().map(|a| a.weekday).collect();
-    let remove: HashSet<Weekday> = existing.difference(&target).cloned().collect();
-    let add: HashSet<Weekday> = target.difference(&existing).cloned().collect();
-    let update: HashSet<Weekday> = target.intersection(&existing).cloned().collect();
+        .filter(|a| !target.contains(&(a.weekday.clone(), a.start, a.end)))
+        .map(|a| a.id)
+        .collect();
+    let add: Vec<&(String, NaiveTime, NaiveTime)> = target.difference(&existing).collect();
     trace!(
-        "availability: existing={:?}, target={:?}, remove={:?}, add={:?}, update={:?}",
-        existing, target, remove, add, update
+        "availability: existing={:?}, target={:?}, remove={:?}, add={:?}",
+        existing, target, remove, add
     );
     if !remove.is_empty() {
         availability::Entity::delete_many()
-            .filter(
-                availability::Column::ResourceId.eq(model.id).and(
-                    availability::Column::Weekday.is_in(
-                        remove
-                            .iter()
-                            .map(|w| {
-                                let wstr: &'static str = w.into();
-                                wstr.to_owned()
-                            })
-                            .collect::<Vec<String>>(),
-                    ),
-                ),
-            )
+            .filter(availability::Column::Id.is_in(remove))
             .exec(txn)
             .await?;
     }
     if !add.is_empty() {
-        let add_models: Vec<availability::ActiveModel> = availability
-            .iter()
-            .filter(|a| add.contains(&a.weekday))
-            .map(|a| {
-                let mut am: availability::ActiveModel = a.into();
-                am.resource_id = ActiveValue::Set(model.id);
-                am
+        let add_models: Vec<availability::ActiveModel> = add
+            .into_iter()
+            .map(|(weekday, start, end)| availability::ActiveModel {
+                id: ActiveValue::NotSet,
+                resource_id: ActiveValue::Set(model.id),
+                weekday: ActiveValue::Set(weekday.clone()),
+                start: ActiveValue::Set(*start),
+                end: ActiveValue::Set(*end),
+                pattern_id: ActiveValue::Set(pattern_id),
             })
             .collect();
         availability::Entity::insert_many(add_models)
             .exec(txn)
             .await?;
     }
-    if !update.is_empty() {
-        let existing_models: Vec<&availability::Model> = existing_availability
-            .iter()
-            .filter(|a| {
-                let wd = a.weekday();
-                if let Ok(wd) = wd {
-                    update.contains(&wd)
-                } else {
-                    false
-                }
-            })
-            .collect();
-        let update_models: Vec<&AvailabilityInput> = availability
-            .iter()
-            .filter(|a| update.contains(&a.weekday))
-            .collect();
-        if existing_models.len() != update_models.len() {
-            return Err(anyhow!("Internal error trying to update the availability."));
-        }
-        let update_models: Vec<availability::ActiveModel> = zip(existing_models, update_models)
-            .filter(|(e, u)| e.duration != Decimal::from(u.duration) / Decimal::from(3600))
-            .map(|(e, u)| {
-                let mut am: availability::ActiveModel = u.into();
-                am.resource_id = ActiveValue::Set(model.id);
-                am.id = ActiveValue::Set(e.id);
-                am
-            })
-            .collect();
-        for am in update_models {
-            am.update(txn).await?;
-        }
-    }
     Ok(())
 }
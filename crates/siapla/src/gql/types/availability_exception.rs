@@ -0,0 +1,70 @@
+use crate::entity::availability_exception;
+use anyhow::anyhow;
+use chrono::{NaiveDate, NaiveTime};
+use juniper::graphql_object;
+use sea_orm::ActiveValue;
+
+#[graphql_object]
+#[graphql(name = "AvailabilityException")]
+impl availability_exception::Model {
+    fn db_id(&self) -> &i32 {
+        &self.id
+    }
+    fn date(&self) -> NaiveDate {
+        self.date
+    }
+    /// Local start time (in the resource's timezone), empty if the resource does not work at all
+    fn start(&self) -> Option<NaiveTime> {
+        self.start
+    }
+    /// Local end time (in the resource's timezone), 00:00 denotes the end of the day
+    fn end(&self) -> Option<NaiveTime> {
+        self.end
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// Working time on a specific date, replacing the regular working time, holidays and vacations
+/// of that day. Several exceptions on the same date are combined. Without start and end, the
+/// resource does not work on that date.
+#[derive(juniper::GraphQLInputObject)]
+pub struct AvailabilityExceptionInput {
+    date: NaiveDate,
+    start: Option<NaiveTime>,
+    end: Option<NaiveTime>,
+    description: Option<String>,
+}
+
+impl TryFrom<AvailabilityExceptionInput> for availability_exception::ActiveModel {
+    type Error = anyhow::Error;
+
+    fn try_from(value: AvailabilityExceptionInput) -> Result<Self, Self::Error> {
+        match (value.start, value.end) {
+            (Some(start), Some(end)) if end <= start && end != NaiveTime::MIN => {
+                return Err(anyhow!(
+                    "Availability exception on {} must end after it starts ({} - {}).",
+                    value.date,
+                    start,
+                    end
+                ));
+            }
+            (Some(_), None) | (None, Some(_)) => {
+                return Err(anyhow!(
+                    "Availability exception on {} needs both start and end or neither.",
+                    value.date
+                ));
+            }
+            _ => {}
+        }
+        Ok(availability_exception::ActiveModel {
+            id: ActiveValue::NotSet,
+            resource_id: ActiveValue::NotSet,
+            date: ActiveValue::Set(value.date),
+            start: ActiveValue::Set(value.start),
+            end: ActiveValue::Set(value.end),
+            description: ActiveValue::Set(value.description.unwrap_or_default()),
+        })
+    }
+}
@@ -0,0 +1,257 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use chrono::{Datelike, NaiveDate, NaiveTime};
+use juniper::{GraphQLEnum, graphql_object};
+use sea_orm::prelude::*;
+use sea_orm::{ActiveValue, DatabaseTransaction};
+use strum::{EnumString, IntoStaticStr};
+
+use crate::{
+    entity::{blocked_time, resource},
+    gql::{common::opt_to_av, dataloader::string_to_weekday},
+};
+
+use super::availability::Weekday;
+
+#[derive(GraphQLEnum, IntoStaticStr, EnumString, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum BlockedTimeFrequency {
+    /// Every `interval` days
+    Daily,
+    /// On the given weekday of every `interval` weeks
+    Weekly,
+}
+
+impl From<BlockedTimeFrequency> for String {
+    fn from(value: BlockedTimeFrequency) -> Self {
+        let s: &'static str = value.into();
+        s.into()
+    }
+}
+
+#[graphql_object]
+#[graphql(name = "BlockedTime")]
+impl blocked_time::Model {
+    fn db_id(&self) -> &i32 {
+        &self.id
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+    fn frequency(&self) -> anyhow::Result<BlockedTimeFrequency> {
+        Ok(BlockedTimeFrequency::from_str(&self.frequency)?)
+    }
+    /// Number of days / weeks between two occurrences, e.g. 2 for bi-weekly
+    fn interval(&self) -> i32 {
+        self.interval
+    }
+    /// Weekday of weekly blocks
+    fn weekday(&self) -> anyhow::Result<Option<Weekday>> {
+        Ok(self.weekday.as_deref().map(Weekday::from_str).transpose()?)
+    }
+    /// Local start time (in the resource's timezone)
+    fn start(&self) -> NaiveTime {
+        self.start
+    }
+    /// Local end time (in the resource's timezone), 00:00 denotes the end of the day
+    fn end(&self) -> NaiveTime {
+        self.end
+    }
+    /// First day this block may occur on, occurrences are counted from here
+    fn valid_from(&self) -> &Option<NaiveDate> {
+        &self.valid_from
+    }
+    /// Last day this block may occur on
+    fn valid_until(&self) -> &Option<NaiveDate> {
+        &self.valid_until
+    }
+}
+
+/// Whether the recurring block occurs on the (local) `date`. Days and weeks are counted from
+/// `valid_from` (weeks start on Monday), or from Monday, 1970-01-05 if it is not set.
+pub fn occurs_on(blocked_time: &blocked_time::Model, date: NaiveDate) -> bool {
+    if blocked_time.valid_from.is_some_and(|from| date < from)
+        || blocked_time.valid_until.is_some_and(|until| until < date)
+    {
+        return false;
+    }
+    let anchor = blocked_time
+        .valid_from
+        .unwrap_or(NaiveDate::from_ymd_opt(1970, 1, 5).expect("Must be a valid date"));
+    let interval = blocked_time.interval.max(1) as i64;
+    match BlockedTimeFrequency::from_str(&blocked_time.frequency) {
+        Ok(BlockedTimeFrequency::Daily) => (date - anchor).num_days().rem_euclid(interval) == 0,
+        Ok(BlockedTimeFrequency::Weekly) => {
+            let Some(weekday) =
+                blocked_time.weekday.as_deref().and_then(|w| string_to_weekday(w).ok())
+            else {
+                return false;
+            };
+            let week_start = |d: NaiveDate| d.week(chrono::Weekday::Mon).first_day();
+            let weeks = (week_start(date) - week_start(anchor)).num_days() / 7;
+            date.weekday() == weekday && weeks.rem_euclid(interval) == 0
+        }
+        Err(_) => false,
+    }
+}
+
+/// Recurring time a resource is not available for tasks, e.g. a meeting every other Monday
+/// from 09:00 to 10:30. Weekly blocks need a weekday.
+#[derive(juniper::GraphQLInputObject, Debug)]
+pub struct BlockedTimeInput {
+    db_id: Option<i32>,
+    description: Option<String>,
+    frequency: BlockedTimeFrequency,
+    interval: Option<i32>,
+    weekday: Option<Weekday>,
+    start: NaiveTime,
+    end: NaiveTime,
+    valid_from: Option<NaiveDate>,
+    valid_until: Option<NaiveDate>,
+}
+
+impl BlockedTimeInput {
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.end <= self.start && self.end != NaiveTime::MIN {
+            return Err(anyhow!(
+                "Blocked time must end after it starts ({} - {}).",
+                self.start,
+                self.end
+            ));
+        }
+        if self.interval.is_some_and(|i| i < 1) {
+            return Err(anyhow!("Blocked time interval must be at least 1."));
+        }
+        if self.frequency == BlockedTimeFrequency::Weekly && self.weekday.is_none() {
+            return Err(anyhow!("Weekly blocked time needs a weekday."));
+        }
+        if let (Some(from), Some(until)) = (self.valid_from, self.valid_until)
+            && until < from
+        {
+            return Err(anyhow!("Blocked time must end after it starts ({} - {}).", from, until));
+        }
+        Ok(())
+    }
+}
+
+/// Replace the recurring blocked times of a resource. Blocked times not contained in
+/// `blocked_times` are removed.
+pub async fn update_blocked_times(
+    txn: &DatabaseTransaction,
+    model: &resource::Model,
+    blocked_times: Vec<BlockedTimeInput>,
+) -> anyhow::Result<()> {
+    let existing: Vec<i32> = blocked_time::Entity::find()
+        .filter(blocked_time::Column::ResourceId.eq(model.id))
+        .all(txn)
+        .await?
+        .into_iter()
+        .map(|b| b.id)
+        .collect();
+    let remove: Vec<i32> = existing
+        .iter()
+        .filter(|id| !blocked_times.iter().any(|b| b.db_id == Some(**id)))
+        .cloned()
+        .collect();
+    if !remove.is_empty() {
+        blocked_time::Entity::delete_many()
+            .filter(blocked_time::Column::Id.is_in(remove))
+            .exec(txn)
+            .await?;
+    }
+    for input in blocked_times {
+        input.validate()?;
+        if let Some(id) = input.db_id
+            && !existing.contains(&id)
+        {
+            return Err(anyhow!("Blocked time {} does not belong to this resource.", id));
+        }
+        let weekday = match input.frequency {
+            BlockedTimeFrequency::Weekly => input.weekday.map(String::from),
+            BlockedTimeFrequency::Daily => None,
+        };
+        let am = blocked_time::ActiveModel {
+            id: opt_to_av!(input.db_id),
+            resource_id: ActiveValue::Set(model.id),
+            description: ActiveValue::Set(input.description.unwrap_or_default()),
+            frequency: ActiveValue::Set(input.frequency.into()),
+            interval: ActiveValue::Set(input.interval.unwrap_or(1)),
+            weekday: ActiveValue::Set(weekday),
+            start: ActiveValue::Set(input.start),
+            end: ActiveValue::Set(input.end),
+            valid_from: ActiveValue::Set(input.valid_from),
+            valid_until: ActiveValue::Set(input.valid_until),
+        };
+        if am.id.is_set() {
+            am.update(txn).await?;
+        } else {
+            am.insert(txn).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn blocked_time(
+        frequency: BlockedTimeFrequency,
+        interval: i32,
+        weekday: Option<Weekday>,
+        valid_from: Option<&str>,
+        valid_until: Option<&str>,
+    ) -> blocked_time::Model {
+        blocked_time::Model {
+            id: 1,
+            resource_id: 1,
+            description: String::new(),
+            frequency: frequency.into(),
+            interval,
+            weekday: weekday.map(Into::into),
+            start: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(10, 30, 0).unwrap(),
+            valid_from: valid_from.map(date),
+            valid_until: valid_until.map(date),
+        }
+    }
+
+    #[test]
+    fn test_occurs_on_daily() {
+        let bt = blocked_time(BlockedTimeFrequency::Daily, 3, None, Some("2023-01-02"), None);
+        assert!(occurs_on(&bt, date("2023-01-02")));
+        assert!(!occurs_on(&bt, date("2023-01-04")));
+        assert!(occurs_on(&bt, date("2023-01-05")));
+        assert!(!occurs_on(&bt, date("2022-12-30")));
+        // without valid_from, days are counted from 1970-01-05
+        let bt = blocked_time(BlockedTimeFrequency::Daily, 2, None, None, Some("2023-01-10"));
+        assert!(occurs_on(&bt, date("1970-01-07")));
+        assert!(!occurs_on(&bt, date("1970-01-08")));
+        assert!(!occurs_on(&bt, date("2023-01-12")));
+    }
+
+    #[test]
+    fn test_occurs_on_weekly() {
+        // every other Monday, weeks counted from the week of valid_from (a Wednesday)
+        let bt = blocked_time(
+            BlockedTimeFrequency::Weekly,
+            2,
+            Some(Weekday::Monday),
+            Some("2023-01-04"),
+            Some("2023-01-31"),
+        );
+        assert!(!occurs_on(&bt, date("2023-01-02")));
+        assert!(!occurs_on(&bt, date("2023-01-09")));
+        assert!(occurs_on(&bt, date("2023-01-16")));
+        assert!(!occurs_on(&bt, date("2023-01-17")));
+        assert!(occurs_on(&bt, date("2023-01-30")));
+        assert!(!occurs_on(&bt, date("2023-02-13")));
+        // weekly blocks without weekday never occur
+        let bt = blocked_time(BlockedTimeFrequency::Weekly, 1, None, None, None);
+        assert!(!occurs_on(&bt, date("2023-01-16")));
+    }
+}
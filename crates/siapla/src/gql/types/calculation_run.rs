@@ -0,0 +1,104 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use juniper::{GraphQLEnum, graphql_object};
+use strum::{EnumString, IntoStaticStr};
+
+use crate::{
+    entity::{calculation_run, calculation_run_cost, task},
+    gql::context::Context,
+};
+
+#[derive(GraphQLEnum, IntoStaticStr, EnumString, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum CalculationTrigger {
+    /// Requested with `recalculateNow`
+    Manual,
+    /// Started automatically after modifications (and on startup)
+    Debounce,
+}
+
+impl From<CalculationTrigger> for String {
+    fn from(value: CalculationTrigger) -> Self {
+        let s: &'static str = value.into();
+        s.into()
+    }
+}
+
+#[derive(GraphQLEnum, IntoStaticStr, EnumString, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum CalculationResult {
+    /// A new plan was stored
+    Finished,
+    /// Cancelled manually or by modifications during the run, the previous plan was kept
+    Cancelled,
+    Failed,
+}
+
+impl From<CalculationResult> for String {
+    fn from(value: CalculationResult) -> Self {
+        let s: &'static str = value.into();
+        s.into()
+    }
+}
+
+#[graphql_object]
+#[graphql(name = "CalculationRun")]
+impl calculation_run::Model {
+    fn db_id(&self) -> &i32 {
+        &self.id
+    }
+    fn trigger(&self) -> anyhow::Result<CalculationTrigger> {
+        Ok(CalculationTrigger::from_str(&self.trigger)?)
+    }
+    fn result(&self) -> anyhow::Result<CalculationResult> {
+        Ok(CalculationResult::from_str(&self.result)?)
+    }
+    fn started_at(&self) -> &DateTime<Utc> {
+        &self.started_at
+    }
+    fn finished_at(&self) -> &DateTime<Utc> {
+        &self.finished_at
+    }
+    /// Settings of the genetic algorithm as JSON
+    fn settings(&self) -> &str {
+        &self.settings
+    }
+    /// Seed of the random number generator, as string because it does not fit into an Int
+    fn seed(&self) -> String {
+        (self.seed as u64).to_string()
+    }
+    /// Total cost of the plan (sum of the milestone costs), none if the run did not finish
+    fn cost(&self) -> Option<f64> {
+        self.cost
+    }
+    /// Number of issues found while planning, none if the run did not finish
+    fn issue_count(&self) -> &Option<i32> {
+        &self.issue_count
+    }
+    /// Cost per milestone, highest first
+    async fn milestone_costs(
+        &self,
+        ctx: &Context,
+    ) -> anyhow::Result<Vec<calculation_run_cost::Model>> {
+        const CIDX: usize = calculation_run_cost::Column::CalculationRunId as usize;
+        let mut costs = ctx.load_by_col::<calculation_run_cost::Entity, CIDX>(self.id).await?;
+        costs.sort_by(|a, b| b.cost.total_cmp(&a.cost));
+        Ok(costs)
+    }
+}
+
+#[graphql_object]
+#[graphql(name = "MilestoneCost")]
+impl calculation_run_cost::Model {
+    /// The milestone, none if it was deleted since
+    async fn milestone(&self, ctx: &Context) -> anyhow::Result<Option<task::Model>> {
+        const CIDX: usize = task::Column::Id as usize;
+        ctx.load_one_by_col::<task::Entity, CIDX>(self.milestone_id).await
+    }
+    /// Title of the milestone at the time of the calculation
+    fn milestone_title(&self) -> &str {
+        &self.milestone_title
+    }
+    fn cost(&self) -> f64 {
+        self.cost
+    }
+}
@@ -0,0 +1,367 @@
+use std::{collections::HashSet, str::FromStr};
+
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use juniper::{GraphQLEnum, graphql_object};
+use sea_orm::prelude::*;
+use sea_orm::{ActiveValue, DatabaseTransaction, IntoActiveModel, QueryOrder as _};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use strum::{EnumString, IntoStaticStr};
+
+use crate::{
+    auth::Role,
+    entity::{
+        allocated_resource, allocation, audit_log, change_set, dependency, project,
+        resource_constraint, resource_constraint_entry, task, user,
+    },
+    gql::context::Context,
+};
+
+use super::{
+    audit_log::{AuditEntityType, audit_value, booking_snapshot, insert_entry},
+    project::touch_project,
+};
+
+#[derive(GraphQLEnum, IntoStaticStr, EnumString, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ChangeSetState {
+    /// The changes are in effect
+    Applied,
+    /// The changes were undone and can be redone
+    Undone,
+    /// The changes were undone and cannot be redone anymore, because later changes followed
+    Discarded,
+}
+
+impl From<ChangeSetState> for String {
+    fn from(value: ChangeSetState) -> Self {
+        let s: &'static str = value.into();
+        s.into()
+    }
+}
+
+#[graphql_object]
+#[graphql(name = "ChangeSet")]
+impl change_set::Model {
+    fn db_id(&self) -> &i32 {
+        &self.id
+    }
+    async fn user(&self, ctx: &Context) -> anyhow::Result<Option<user::Model>> {
+        const CIDX: usize = user::Column::Id as usize;
+        ctx.load_one_by_col::<user::Entity, CIDX>(self.user_id).await
+    }
+    fn state(&self) -> anyhow::Result<ChangeSetState> {
+        Ok(ChangeSetState::from_str(&self.state)?)
+    }
+    fn created_at(&self) -> &DateTime<Utc> {
+        &self.created_at
+    }
+    /// The changes in the order they were made
+    async fn entries(&self, ctx: &Context) -> anyhow::Result<Vec<audit_log::Model>> {
+        const CIDX: usize = audit_log::Column::ChangeSetId as usize;
+        let mut entries = ctx.load_by_col::<audit_log::Entity, CIDX>(self.id).await?;
+        entries.sort_by_key(|e| e.id);
+        Ok(entries)
+    }
+}
+
+/// Change sets of the current user, without login the ones made without login
+fn own_change_sets(ctx: &Context) -> sea_orm::sea_query::SimpleExpr {
+    match ctx.user() {
+        Some(user) => change_set::Column::UserId.eq(user.id),
+        None => change_set::Column::UserId.is_null(),
+    }
+}
+
+/// Used by `Context::change_set`. New changes discard the undone changes of the user, they can
+/// no longer be redone.
+pub(crate) async fn create_change_set(ctx: &Context) -> anyhow::Result<i32> {
+    let txn = ctx.txn().await?;
+    change_set::Entity::update_many()
+        .col_expr(change_set::Column::State, Expr::value(String::from(ChangeSetState::Discarded)))
+        .filter(own_change_sets(ctx))
+        .filter(change_set::Column::State.eq(<&'static str>::from(ChangeSetState::Undone)))
+        .exec(txn)
+        .await?;
+    let am = change_set::ActiveModel {
+        id: ActiveValue::NotSet,
+        user_id: ActiveValue::Set(ctx.user().map(|u| u.id)),
+        state: ActiveValue::Set(ChangeSetState::Applied.into()),
+        created_at: ActiveValue::Set(Utc::now()),
+    };
+    Ok(am.insert(txn).await?.id)
+}
+
+/// The latest change set of the current user that `undo` reverts
+pub async fn undoable(ctx: &Context) -> anyhow::Result<Option<change_set::Model>> {
+    let res = change_set::Entity::find()
+        .filter(own_change_sets(ctx))
+        .filter(change_set::Column::State.eq(<&'static str>::from(ChangeSetState::Applied)))
+        .order_by_desc(change_set::Column::Id)
+        .one(ctx.txn().await?)
+        .await?;
+    Ok(res)
+}
+
+/// The most recently undone change set of the current user that `redo` applies again
+pub async fn redoable(ctx: &Context) -> anyhow::Result<Option<change_set::Model>> {
+    let res = change_set::Entity::find()
+        .filter(own_change_sets(ctx))
+        .filter(change_set::Column::State.eq(<&'static str>::from(ChangeSetState::Undone)))
+        .order_by_asc(change_set::Column::Id)
+        .one(ctx.txn().await?)
+        .await?;
+    Ok(res)
+}
+
+/// Revert the last change set of the current user, none if there is nothing to undo
+pub async fn undo(ctx: &Context) -> anyhow::Result<Option<change_set::Model>> {
+    let Some(change_set) = undoable(ctx).await? else {
+        return Ok(None);
+    };
+    replay(ctx, &change_set, true).await?;
+    set_state(ctx, change_set, ChangeSetState::Undone).await.map(Some)
+}
+
+/// Apply the last undone change set of the current user again, none if there is nothing to redo
+pub async fn redo(ctx: &Context) -> anyhow::Result<Option<change_set::Model>> {
+    let Some(change_set) = redoable(ctx).await? else {
+        return Ok(None);
+    };
+    replay(ctx, &change_set, false).await?;
+    set_state(ctx, change_set, ChangeSetState::Applied).await.map(Some)
+}
+
+async fn set_state(
+    ctx: &Context,
+    change_set: change_set::Model,
+    state: ChangeSetState,
+) -> anyhow::Result<change_set::Model> {
+    let mut am: change_set::ActiveModel = change_set.into();
+    am.state = ActiveValue::Set(state.into());
+    Ok(am.update(ctx.txn().await?).await?)
+}
+
+/// Restore the states before (`undo`) or after the changes of a change set. Every entity must
+/// still be in the state the change set left it in, otherwise later changes would be lost.
+async fn replay(ctx: &Context, change_set: &change_set::Model, undo: bool) -> anyhow::Result<()> {
+    let txn = ctx.txn().await?;
+    let mut entries = audit_log::Entity::find()
+        .filter(audit_log::Column::ChangeSetId.eq(change_set.id))
+        .order_by_asc(audit_log::Column::Id)
+        .all(txn)
+        .await?;
+    let entity_types = entries
+        .iter()
+        .map(|e| AuditEntityType::from_str(&e.entity_type))
+        .collect::<Result<Vec<_>, _>>()?;
+    // members may only undo their own bookings
+    if entity_types.iter().all(|t| *t == AuditEntityType::Booking) {
+        ctx.require_role(Role::Member)?;
+    } else {
+        ctx.require_role(Role::Planner)?;
+    }
+    if undo {
+        entries.reverse();
+    }
+    let parse = |json: &Option<String>| -> anyhow::Result<Option<Value>> {
+        Ok(json.as_deref().map(serde_json::from_str).transpose()?)
+    };
+    let mut projects = HashSet::new();
+    for entry in entries {
+        let entity_type = AuditEntityType::from_str(&entry.entity_type)?;
+        let (expected, target) = if undo {
+            (parse(&entry.after)?, parse(&entry.before)?)
+        } else {
+            (parse(&entry.before)?, parse(&entry.after)?)
+        };
+        let current = current_state(txn, entity_type, entry.entity_id).await?;
+        if without_version(&current) != without_version(&expected) {
+            let name: &'static str = entity_type.into();
+            return Err(anyhow!(
+                "{} {} was changed afterwards, the changes cannot be {}",
+                name,
+                entry.entity_id,
+                if undo { "undone" } else { "redone" }
+            ));
+        }
+        let target = next_version(target, [&current, &expected]);
+        restore(txn, entity_type, entry.entity_id, current.is_some(), target.clone()).await?;
+        match entity_type {
+            AuditEntityType::Task => projects.extend(
+                [&current, &target]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|state| state["project_id"].as_i64())
+                    .map(|id| id as i32),
+            ),
+            AuditEntityType::Project if target.is_some() => {
+                projects.insert(entry.entity_id);
+            }
+            _ => {}
+        }
+        insert_entry(ctx, None, entity_type, entry.entity_id, current, target).await?;
+    }
+    for project_id in projects {
+        touch_project(txn, project_id).await?;
+    }
+    Ok(())
+}
+
+/// State without the version of versioned entities (tasks, resources), undo and redo only
+/// move the version forward
+fn without_version(state: &Option<Value>) -> Option<Value> {
+    let mut state = state.clone();
+    if let Some(Value::Object(fields)) = &mut state {
+        fields.remove("version");
+    }
+    state
+}
+
+/// Give a restored state a version above all versions seen so far, so clients holding an older
+/// state get a conflict instead of overwriting the restored one
+fn next_version<const N: usize>(target: Option<Value>, seen: [&Option<Value>; N]) -> Option<Value> {
+    let mut target = target;
+    let Some(Value::Object(fields)) = &mut target else {
+        return target;
+    };
+    let Some(version) = fields.get("version").and_then(Value::as_i64) else {
+        return target;
+    };
+    let latest = seen
+        .into_iter()
+        .flatten()
+        .filter_map(|state| state["version"].as_i64())
+        .fold(version, i64::max);
+    fields.insert("version".to_owned(), Value::from(latest + 1));
+    target
+}
+
+async fn current_state(
+    txn: &DatabaseTransaction,
+    entity_type: AuditEntityType,
+    id: i32,
+) -> anyhow::Result<Option<Value>> {
+    match entity_type {
+        AuditEntityType::Task => state::<task::Entity>(txn, id).await,
+        AuditEntityType::Project => state::<project::Entity>(txn, id).await,
+        AuditEntityType::Dependency => state::<dependency::Entity>(txn, id).await,
+        AuditEntityType::ResourceConstraint => state::<resource_constraint::Entity>(txn, id).await,
+        AuditEntityType::ResourceConstraintEntry => {
+            state::<resource_constraint_entry::Entity>(txn, id).await
+        }
+        AuditEntityType::Booking => booking_snapshot(txn, id).await,
+        AuditEntityType::Resource
+        | AuditEntityType::Team
+        | AuditEntityType::User
+        | AuditEntityType::Holiday => {
+            let name: &'static str = entity_type.into();
+            Err(anyhow!("Changes of {} entities cannot be undone", name))
+        }
+    }
+}
+
+async fn state<E>(txn: &DatabaseTransaction, id: i32) -> anyhow::Result<Option<Value>>
+where
+    E: EntityTrait,
+    E::Model: Serialize,
+    <E::PrimaryKey as PrimaryKeyTrait>::ValueType: From<i32>,
+{
+    E::find_by_id(id).one(txn).await?.as_ref().map(audit_value).transpose()
+}
+
+async fn restore(
+    txn: &DatabaseTransaction,
+    entity_type: AuditEntityType,
+    id: i32,
+    exists: bool,
+    target: Option<Value>,
+) -> anyhow::Result<()> {
+    match entity_type {
+        AuditEntityType::Task => restore_row::<task::Entity>(txn, id, exists, target).await,
+        AuditEntityType::Project => restore_row::<project::Entity>(txn, id, exists, target).await,
+        AuditEntityType::Dependency => {
+            restore_row::<dependency::Entity>(txn, id, exists, target).await
+        }
+        AuditEntityType::ResourceConstraint => {
+            restore_row::<resource_constraint::Entity>(txn, id, exists, target).await
+        }
+        AuditEntityType::ResourceConstraintEntry => {
+            restore_row::<resource_constraint_entry::Entity>(txn, id, exists, target).await
+        }
+        AuditEntityType::Booking => {
+            let resources: Vec<i32> = match &target {
+                Some(target) => serde_json::from_value(target["resources"].clone())?,
+                None => vec![],
+            };
+            allocated_resource::Entity::delete_many()
+                .filter(allocated_resource::Column::AllocationId.eq(id))
+                .exec(txn)
+                .await?;
+            restore_row::<allocation::Entity>(txn, id, exists, target).await?;
+            for resource_id in resources {
+                let am = allocated_resource::ActiveModel {
+                    id: ActiveValue::NotSet,
+                    allocation_id: ActiveValue::Set(id),
+                    resource_id: ActiveValue::Set(resource_id),
+                };
+                am.insert(txn).await?;
+            }
+            Ok(())
+        }
+        AuditEntityType::Resource
+        | AuditEntityType::Team
+        | AuditEntityType::User
+        | AuditEntityType::Holiday => Err(anyhow!("Changes of this entity cannot be undone")),
+    }
+}
+
+/// Bring a row into the recorded state, deleted rows are inserted again with their old id
+async fn restore_row<E>(
+    txn: &DatabaseTransaction,
+    id: i32,
+    exists: bool,
+    target: Option<Value>,
+) -> anyhow::Result<()>
+where
+    E: EntityTrait,
+    E::Model: DeserializeOwned + IntoActiveModel<E::ActiveModel>,
+    E::ActiveModel: ActiveModelBehavior + Send,
+    <E::PrimaryKey as PrimaryKeyTrait>::ValueType: From<i32>,
+{
+    match target {
+        None => {
+            E::delete_by_id(id).exec(txn).await?;
+        }
+        Some(target) => {
+            let am = E::ActiveModel::from_json(target)?;
+            if exists {
+                am.update(txn).await?;
+            } else {
+                E::insert(am).exec(txn).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_next_version() {
+        let before = Some(json!({"id": 1, "title": "a", "version": 3}));
+        let after = Some(json!({"id": 1, "title": "b", "version": 4}));
+        // undo: restores `before` on top of `after`
+        let restored = next_version(before.clone(), [&after, &after]);
+        assert_eq!(restored, Some(json!({"id": 1, "title": "a", "version": 5})));
+        assert_eq!(without_version(&restored), without_version(&before));
+        assert_ne!(without_version(&after), without_version(&before));
+        // entities without a version are restored as recorded
+        let dependency = Some(json!({"id": 2, "predecessor_id": 1}));
+        assert_eq!(next_version(dependency.clone(), [&None, &None]), dependency);
+        assert_eq!(next_version(None, [&after, &after]), None);
+    }
+}
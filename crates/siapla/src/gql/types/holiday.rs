@@ -1,21 +1,58 @@
-use std::{collections::HashMap, sync::OnceLock};
+use std::{collections::HashMap, str::FromStr, sync::OnceLock};
 
 use anyhow::anyhow;
-use juniper::graphql_object;
+use chrono::{DateTime, NaiveDate, Utc};
+use itertools::Itertools as _;
+use juniper::{GraphQLEnum, graphql_object};
 use sea_orm::{
-    ActiveValue, ColumnTrait, DatabaseTransaction, EntityTrait, Order, QueryFilter, QueryOrder,
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseTransaction, EntityTrait, ModelTrait,
+    Order, QueryFilter, QueryOrder,
 };
 use tokio::sync::OnceCell;
-use tracing::error;
+use tracing::{error, warn};
 
 use crate::{
     entity::{holiday, holiday_entry},
-    gql::context::Context,
+    gql::{
+        audit_log::{AuditEntityType, holiday_snapshot, record_change},
+        context::Context,
+    },
+    holidays::{self, HolidayProvider},
 };
 
-use siapla_open_holidays_api::apis::{configuration::Configuration, holidays_api, regional_api};
+use strum::{EnumString, IntoStaticStr};
 //
 
+/// Kind of a holiday entry, public holidays and school holidays are cached side by side
+#[derive(GraphQLEnum, IntoStaticStr, EnumString, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum HolidayEntryType {
+    Public,
+    School,
+}
+
+impl From<HolidayEntryType> for String {
+    fn from(value: HolidayEntryType) -> Self {
+        let s: &'static str = value.into();
+        s.into()
+    }
+}
+
+/// How school holidays affect the availability of a resource subscribed to them
+#[derive(GraphQLEnum, IntoStaticStr, EnumString, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum SchoolHolidayMode {
+    /// Likely reduced availability: the second half of each day's working time is off
+    Reduced,
+    /// School holidays are treated like vacation
+    Vacation,
+}
+
+impl From<SchoolHolidayMode> for String {
+    fn from(value: SchoolHolidayMode) -> Self {
+        let s: &'static str = value.into();
+        s.into()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Country {
     pub isocode: String,
@@ -31,22 +68,15 @@ impl Country {
     fn name(&self) -> &str {
         &self.name
     }
-    pub async fn regions(&self, _ctx: &Context) -> anyhow::Result<Vec<Region>> {
-        let subdivisions = regional_api::subdivisions_get(
-            &Configuration {
-                base_path: "https://openholidaysapi.org".into(),
-                ..Default::default()
-            },
-            &self.isocode,
-            "EN".into(),
-        )
-        .await?;
+    pub async fn regions(&self, ctx: &Context) -> anyhow::Result<Vec<Region>> {
+        let subdivisions =
+            holidays::catalogue::subdivisions(ctx.txn().await?, &self.isocode).await?;
         Ok(subdivisions
             .into_iter()
             .map(|sd| Region {
                 isocode: sd.code,
                 country_name: self.name.clone(),
-                region_name: sd.name.first().expect("API did not return region name").text.clone(),
+                region_name: sd.name,
             })
             .collect())
     }
@@ -100,6 +130,21 @@ impl GQLHoliday {
     async fn name(&self, ctx: &Context) -> anyhow::Result<&str> {
         Ok(&self.get_model(ctx).await?.name)
     }
+    async fn provider(&self, ctx: &Context) -> anyhow::Result<HolidayProvider> {
+        Ok(HolidayProvider::from_str(&self.get_model(ctx).await?.provider)?)
+    }
+    /// Language of the entry names (ISO 639-1 code)
+    async fn language(&self, ctx: &Context) -> anyhow::Result<&str> {
+        Ok(&self.get_model(ctx).await?.language)
+    }
+    /// Last time the cached entries were downloaded
+    async fn refreshed_at(&self, ctx: &Context) -> anyhow::Result<Option<DateTime<Utc>>> {
+        Ok(self.get_model(ctx).await?.refreshed_at)
+    }
+    /// Global calendars (e.g. company shutdown days) apply to all resources
+    async fn global(&self, ctx: &Context) -> anyhow::Result<bool> {
+        Ok(self.get_model(ctx).await?.global)
+    }
 
     async fn country(&self, _ctx: &Context) -> Option<Country> {
         // If the isocode is 2 characters, it's a country code
@@ -146,7 +191,23 @@ impl GQLHoliday {
             .inspect_err(|e| error!("error on GQLHoliday::get_model: {:?}", e))?;
         let txn = ctx.txn().await?;
         model
-            .ensure_entries(txn, from, until)
+            .ensure_entries(txn, HolidayEntryType::Public, from, until)
+            .await
+            .inspect_err(|e| error!("error on GQLHoliday::ensure_entries: {:?}", e))
+    }
+    pub async fn school_entries(
+        &self,
+        ctx: &Context,
+        from: chrono::NaiveDate,
+        until: chrono::NaiveDate,
+    ) -> anyhow::Result<Vec<holiday_entry::Model>> {
+        let model = self
+            .get_model(ctx)
+            .await
+            .inspect_err(|e| error!("error on GQLHoliday::get_model: {:?}", e))?;
+        let txn = ctx.txn().await?;
+        model
+            .ensure_entries(txn, HolidayEntryType::School, from, until)
             .await
             .inspect_err(|e| error!("error on GQLHoliday::ensure_entries: {:?}", e))
     }
@@ -164,6 +225,14 @@ impl holiday_entry::Model {
     fn name(&self) -> Option<&str> {
         self.name.as_deref()
     }
+    /// Part of the day's working time that is off, e.g. 0.5 for a half-day holiday. Partial
+    /// holidays take off the end of the working day.
+    fn fraction(&self) -> f64 {
+        self.fraction as f64
+    }
+    fn r#type(&self) -> anyhow::Result<HolidayEntryType> {
+        Ok(HolidayEntryType::from_str(&self.r#type)?)
+    }
     async fn holiday(&self, ctx: &Context) -> anyhow::Result<GQLHoliday> {
         const CIDX: usize = holiday::Column::Id as usize;
         let model =
@@ -193,18 +262,45 @@ impl GQLHoliday {
     }
 }
 
+/// Downloaded entries of a range whose entries are cached
+struct KnownEntries {
+    entry_type: HolidayEntryType,
+    from: chrono::NaiveDate,
+    until: chrono::NaiveDate,
+    provided: Vec<holidays::ProvidedEntry>,
+}
+
 impl holiday::Model {
+    /// Make sure all entries of the given type between `from` and `until` have been downloaded
+    /// and return them.
     pub async fn ensure_entries(
         &self,
         txn: &DatabaseTransaction,
+        entry_type: HolidayEntryType,
         from: chrono::NaiveDate,
         until: chrono::NaiveDate,
     ) -> anyhow::Result<Vec<holiday_entry::Model>> {
-        match (self.start, self.end) {
+        if holidays::is_stale(self) {
+            // outdated entries are better than none, e.g. while offline. Nothing is written
+            // before all entries are downloaded, so only download errors can be ignored.
+            match self.fetch_known_entries().await {
+                Ok(Some(provided)) => self.replace_known_entries(txn, provided).await?,
+                Ok(None) => {}
+                Err(err) => {
+                    warn!("Failed to refresh holiday {} (id: {}): {:?}", self.name, self.id, err)
+                }
+            }
+        }
+        let (known_start, known_end) = match entry_type {
+            HolidayEntryType::Public => (self.start, self.end),
+            HolidayEntryType::School => (self.school_start, self.school_end),
+        };
+        let (start, end) = match (known_start, known_end) {
             (Some(start), Some(end)) => {
                 if start > from {
                     self.download_entries(
                         txn,
+                        entry_type,
                         from,
                         start.pred_opt().ok_or(anyhow!("Not a representable date"))?,
                     )
@@ -214,35 +310,41 @@ impl holiday::Model {
                 if end < until {
                     self.download_entries(
                         txn,
+                        entry_type,
                         end.succ_opt().ok_or(anyhow!("Not a representable date"))?,
                         until,
                     )
                     .await?;
                 }
-                holiday::Entity::update(holiday::ActiveModel {
-                    id: ActiveValue::Set(self.id),
-                    start: ActiveValue::Set(Some(start.min(from))),
-                    end: ActiveValue::Set(Some(end.max(until))),
-                    ..Default::default()
-                })
-                .exec(txn)
-                .await?;
+                (start.min(from), end.max(until))
             }
             _ => {
-                self.download_entries(txn, from, until).await?;
-                holiday::Entity::update(holiday::ActiveModel {
-                    id: ActiveValue::Set(self.id),
-                    start: ActiveValue::Set(Some(from)),
-                    end: ActiveValue::Set(Some(until)),
-                    ..Default::default()
-                })
-                .exec(txn)
-                .await?;
+                self.download_entries(txn, entry_type, from, until).await?;
+                (from, until)
             }
+        };
+        let mut am = match entry_type {
+            HolidayEntryType::Public => holiday::ActiveModel {
+                id: ActiveValue::Set(self.id),
+                start: ActiveValue::Set(Some(start)),
+                end: ActiveValue::Set(Some(end)),
+                ..Default::default()
+            },
+            HolidayEntryType::School => holiday::ActiveModel {
+                id: ActiveValue::Set(self.id),
+                school_start: ActiveValue::Set(Some(start)),
+                school_end: ActiveValue::Set(Some(end)),
+                ..Default::default()
+            },
+        };
+        if self.refreshed_at.is_none() {
+            am.refreshed_at = ActiveValue::Set(Some(Utc::now()));
         }
+        holiday::Entity::update(am).exec(txn).await?;
 
         Ok(holiday_entry::Entity::find()
             .filter(holiday_entry::Column::HolidayId.eq(self.id))
+            .filter(holiday_entry::Column::Type.eq(String::from(entry_type)))
             .filter(holiday_entry::Column::Date.gte(from))
             .filter(holiday_entry::Column::Date.lte(until))
             .order_by(holiday_entry::Column::Date, Order::Asc)
@@ -253,43 +355,29 @@ impl holiday::Model {
     async fn download_entries(
         &self,
         txn: &DatabaseTransaction,
+        entry_type: HolidayEntryType,
         from: chrono::NaiveDate,
         until: chrono::NaiveDate,
     ) -> anyhow::Result<()> {
-        // https://openholidaysapi.org/PublicHolidays?countryIsoCode=DE&languageIsoCode=EN&validFrom=2025-01-01&validTo=2025-12-31
-        let config =
-            Configuration { base_path: "https://openholidaysapi.org".into(), ..Default::default() };
-        let entries = holidays_api::public_holidays_get(
-            &config,
-            &self.external_id[0..2],
-            from,
-            until,
-            "EN".into(),
-            Some(&self.external_id),
-        )
-        .await?;
+        let provider = HolidayProvider::from_str(&self.provider)?;
+        let entries = provider.fetch_entries(self, entry_type, from, until).await?;
+        self.insert_entries(txn, entry_type, entries).await
+    }
 
+    async fn insert_entries(
+        &self,
+        txn: &DatabaseTransaction,
+        entry_type: HolidayEntryType,
+        entries: Vec<holidays::ProvidedEntry>,
+    ) -> anyhow::Result<()> {
         let new_entries = entries
-            .iter()
-            .flat_map(|e| {
-                let mut result = vec![];
-                if e.nationwide
-                    || e.subdivisions.iter().flatten().any(|d| d.code == self.external_id)
-                {
-                    let mut start = e.start_date;
-                    while start <= e.end_date {
-                        result.push(holiday_entry::ActiveModel {
-                            date: ActiveValue::Set(start),
-                            holiday_id: ActiveValue::Set(self.id),
-                            name: ActiveValue::Set(Some(
-                                e.name.first().map(|n| n.text.clone()).unwrap_or("".into()),
-                            )),
-                            ..Default::default()
-                        });
-                        start = start.succ_opt().expect("Should always be representable");
-                    }
-                }
-                result.into_iter()
+            .into_iter()
+            .map(|e| holiday_entry::ActiveModel {
+                date: ActiveValue::Set(e.date),
+                holiday_id: ActiveValue::Set(self.id),
+                name: ActiveValue::Set(Some(e.name)),
+                r#type: ActiveValue::Set(entry_type.into()),
+                ..Default::default()
             })
             .collect::<Vec<_>>();
 
@@ -304,6 +392,144 @@ impl holiday::Model {
         Ok(())
     }
 
+    /// Download all known entries again and update the cache: new entries are added, entries
+    /// that no longer exist are removed and renamed ones are updated. Fractions set by the
+    /// user are kept. Only entries downloaded on demand can be refreshed.
+    pub async fn refresh(&self, txn: &DatabaseTransaction) -> anyhow::Result<()> {
+        if let Some(provided) = self.fetch_known_entries().await? {
+            self.replace_known_entries(txn, provided).await?;
+        }
+        Ok(())
+    }
+
+    /// Download the entries of all known ranges, `None` if the entries cannot be refreshed
+    async fn fetch_known_entries(&self) -> anyhow::Result<Option<Vec<KnownEntries>>> {
+        let provider = HolidayProvider::from_str(&self.provider)?;
+        if provider != HolidayProvider::OpenHolidays {
+            return Ok(None);
+        }
+        let ranges = [
+            (HolidayEntryType::Public, self.start.zip(self.end)),
+            (HolidayEntryType::School, self.school_start.zip(self.school_end)),
+        ];
+        let mut result = vec![];
+        for (entry_type, range) in ranges {
+            let Some((from, until)) = range else {
+                continue;
+            };
+            let provided = provider.fetch_entries(self, entry_type, from, until).await?;
+            result.push(KnownEntries { entry_type, from, until, provided });
+        }
+        Ok(Some(result))
+    }
+
+    /// Update the cache to the downloaded entries (see `refresh`)
+    async fn replace_known_entries(
+        &self,
+        txn: &DatabaseTransaction,
+        known: Vec<KnownEntries>,
+    ) -> anyhow::Result<()> {
+        for KnownEntries { entry_type, from, until, provided } in known {
+            let mut existing = holiday_entry::Entity::find()
+                .filter(holiday_entry::Column::HolidayId.eq(self.id))
+                .filter(holiday_entry::Column::Type.eq(String::from(entry_type)))
+                .filter(holiday_entry::Column::Date.gte(from))
+                .filter(holiday_entry::Column::Date.lte(until))
+                .order_by(holiday_entry::Column::Id, Order::Asc)
+                .all(txn)
+                .await?
+                .into_iter()
+                .into_group_map_by(|e| e.date);
+
+            let mut new_entries = vec![];
+            for entry in provided {
+                let kept = existing
+                    .get_mut(&entry.date)
+                    .and_then(|entries| (!entries.is_empty()).then(|| entries.remove(0)));
+                match kept {
+                    Some(kept) if kept.name.as_deref() != Some(entry.name.as_str()) => {
+                        holiday_entry::ActiveModel {
+                            id: ActiveValue::Set(kept.id),
+                            name: ActiveValue::Set(Some(entry.name)),
+                            ..Default::default()
+                        }
+                        .update(txn)
+                        .await?;
+                    }
+                    Some(_) => {}
+                    None => new_entries.push(entry),
+                }
+            }
+            let removed = existing.into_values().flatten().map(|e| e.id).collect::<Vec<_>>();
+            if !removed.is_empty() {
+                holiday_entry::Entity::delete_many()
+                    .filter(holiday_entry::Column::Id.is_in(removed))
+                    .exec(txn)
+                    .await?;
+            }
+            self.insert_entries(txn, entry_type, new_entries).await?;
+        }
+        holiday::ActiveModel {
+            id: ActiveValue::Set(self.id),
+            refreshed_at: ActiveValue::Set(Some(Utc::now())),
+            ..Default::default()
+        }
+        .update(txn)
+        .await?;
+        Ok(())
+    }
+
+    /// Create a holiday calendar of the given provider, not backed by the OpenHolidays API
+    pub async fn create_local(
+        txn: &DatabaseTransaction,
+        provider: HolidayProvider,
+        name: String,
+    ) -> anyhow::Result<holiday::Model> {
+        let prefix: &'static str = provider.into();
+        let am = holiday::ActiveModel {
+            external_id: ActiveValue::Set(format!("{}:{}", prefix.to_lowercase(), name)),
+            name: ActiveValue::Set(name),
+            provider: ActiveValue::Set(provider.into()),
+            ..Default::default()
+        };
+        Ok(am.insert(txn).await?)
+    }
+
+    /// Replace all entries of the given type with the events of an iCalendar file. The whole
+    /// range covered by the file counts as known, there is nothing to download later on.
+    pub async fn import_ics(
+        &self,
+        txn: &DatabaseTransaction,
+        entry_type: HolidayEntryType,
+        content: &str,
+    ) -> anyhow::Result<()> {
+        let entries = holidays::parse_ics(content)?;
+        holiday_entry::Entity::delete_many()
+            .filter(holiday_entry::Column::HolidayId.eq(self.id))
+            .filter(holiday_entry::Column::Type.eq(String::from(entry_type)))
+            .exec(txn)
+            .await?;
+        let range = entries.first().zip(entries.last()).map(|(f, l)| (f.date, l.date));
+        self.insert_entries(txn, entry_type, entries).await?;
+        let (start, end) = (range.map(|r| r.0), range.map(|r| r.1));
+        let am = match entry_type {
+            HolidayEntryType::Public => holiday::ActiveModel {
+                id: ActiveValue::Set(self.id),
+                start: ActiveValue::Set(start),
+                end: ActiveValue::Set(end),
+                ..Default::default()
+            },
+            HolidayEntryType::School => holiday::ActiveModel {
+                id: ActiveValue::Set(self.id),
+                school_start: ActiveValue::Set(start),
+                school_end: ActiveValue::Set(end),
+                ..Default::default()
+            },
+        };
+        holiday::Entity::update(am).exec(txn).await?;
+        Ok(())
+    }
+
     pub async fn get_from_open_holidays(
         txn: &DatabaseTransaction,
         isocode: String,
@@ -320,25 +546,12 @@ impl holiday::Model {
                     .ok_or(anyhow!("Unknown country code: {}", &isocode[0..2]))?;
                 let mut name = country_name.clone();
                 if isocode.len() > 2 {
-                    let subdivisions = regional_api::subdivisions_get(
-                        &Configuration {
-                            base_path: "https://openholidaysapi.org".into(),
-                            ..Default::default()
-                        },
-                        &isocode[0..2],
-                        "EN".into(),
-                    )
-                    .await?;
+                    let subdivisions =
+                        holidays::catalogue::subdivisions(txn, &isocode[0..2]).await?;
                     let subdividion_name = subdivisions
                         .into_iter()
-                        .filter_map(|sub| {
-                            if sub.iso_code.unwrap_or(None).unwrap_or("".into()) == isocode {
-                                sub.name.first().map(|n| n.text.clone())
-                            } else {
-                                None
-                            }
-                        })
-                        .next();
+                        .find(|sub| sub.iso_code.as_deref() == Some(isocode.as_str()))
+                        .map(|sub| sub.name);
                     let sub_name = match subdividion_name {
                         None => Err(anyhow!("Isocode for country/region '{}' unknown", isocode))?,
                         Some(sub_name) => sub_name,
@@ -362,6 +575,202 @@ impl holiday::Model {
     }
 }
 
+/// Import a holiday calendar from the content of an iCalendar file, either into a new calendar
+/// or replacing the entries of an existing one.
+pub async fn holiday_import_ics(
+    ctx: &Context,
+    db_id: Option<i32>,
+    name: String,
+    content: String,
+    entry_type: Option<HolidayEntryType>,
+) -> anyhow::Result<GQLHoliday> {
+    let txn = ctx.txn().await?;
+    let before = match db_id {
+        Some(db_id) => holiday_snapshot(txn, db_id).await?,
+        None => None,
+    };
+    let model = match db_id {
+        Some(db_id) => {
+            let model = holiday::Entity::find_by_id(db_id)
+                .one(txn)
+                .await?
+                .ok_or(anyhow!("Failed to find a holiday with id {}", db_id))?;
+            if model.provider != String::from(HolidayProvider::Ics) {
+                return Err(anyhow!("Holiday '{}' is not imported from a file", model.name));
+            }
+            model
+        }
+        None => holiday::Model::create_local(txn, HolidayProvider::Ics, name).await?,
+    };
+    model.import_ics(txn, entry_type.unwrap_or(HolidayEntryType::Public), &content).await?;
+    record_holiday_change(ctx, model.id, before).await?;
+    let model = holiday::Entity::find_by_id(model.id)
+        .one(txn)
+        .await?
+        .ok_or(anyhow!("Failed to find a holiday with id {}", model.id))?;
+    Ok(GQLHoliday::from_model(model))
+}
+
+/// Refresh the cached entries of a holiday calendar, optionally switching the language of the
+/// entry names first.
+pub async fn holiday_refresh(
+    ctx: &Context,
+    db_id: i32,
+    language: Option<String>,
+) -> anyhow::Result<GQLHoliday> {
+    let txn = ctx.txn().await?;
+    let before = holiday_snapshot(txn, db_id).await?;
+    let mut model = holiday::Entity::find_by_id(db_id)
+        .one(txn)
+        .await?
+        .ok_or(anyhow!("Failed to find a holiday with id {}", db_id))?;
+    if let Some(language) = language {
+        if language.len() != 2 || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(anyhow!(
+                "Language must be a two letter ISO 639-1 code, got '{}'",
+                language
+            ));
+        }
+        let mut am: holiday::ActiveModel = model.into();
+        am.language = ActiveValue::Set(language.to_ascii_uppercase());
+        model = am.update(txn).await?;
+    }
+    model.refresh(txn).await?;
+    record_holiday_change(ctx, db_id, before).await?;
+    let model = holiday::Entity::find_by_id(db_id)
+        .one(txn)
+        .await?
+        .ok_or(anyhow!("Failed to find a holiday with id {}", db_id))?;
+    Ok(GQLHoliday::from_model(model))
+}
+
+/// Result of refreshing the subdivision catalogue
+pub struct GQLCatalogueRefresh {
+    pub stored: i32,
+    pub failed_countries: Vec<String>,
+}
+
+#[graphql_object]
+#[graphql(name = "CatalogueRefresh")]
+impl GQLCatalogueRefresh {
+    /// Number of stored subdivisions
+    pub fn stored(&self) -> i32 {
+        self.stored
+    }
+    /// Isocodes of the countries whose download failed, their stored subdivisions are kept
+    pub fn failed_countries(&self) -> &Vec<String> {
+        &self.failed_countries
+    }
+}
+
+/// Create an empty holiday calendar whose entries are entered by hand
+pub async fn holiday_create_manual(
+    ctx: &Context,
+    name: String,
+    global: bool,
+) -> anyhow::Result<GQLHoliday> {
+    let txn = ctx.txn().await?;
+    let mut model = holiday::Model::create_local(txn, HolidayProvider::Manual, name).await?;
+    if global {
+        let mut am: holiday::ActiveModel = model.into();
+        am.global = ActiveValue::Set(true);
+        model = am.update(txn).await?;
+    }
+    record_holiday_change(ctx, model.id, None).await?;
+    Ok(GQLHoliday::from_model(model))
+}
+
+/// Add a day off to a manually maintained holiday calendar, the fraction defaults to a full day
+pub async fn holiday_entry_add(
+    ctx: &Context,
+    holiday_id: i32,
+    date: NaiveDate,
+    name: Option<String>,
+    fraction: Option<f64>,
+    entry_type: Option<HolidayEntryType>,
+) -> anyhow::Result<holiday_entry::Model> {
+    let fraction = fraction.unwrap_or(1.0);
+    if !(fraction > 0.0 && fraction <= 1.0) {
+        return Err(anyhow!("Holiday fraction must be in (0, 1], got {}", fraction));
+    }
+    let txn = ctx.txn().await?;
+    let model = holiday::Entity::find_by_id(holiday_id)
+        .one(txn)
+        .await?
+        .ok_or(anyhow!("Failed to find a holiday with id {}", holiday_id))?;
+    if model.provider != String::from(HolidayProvider::Manual) {
+        return Err(anyhow!("Holiday '{}' is not maintained manually", model.name));
+    }
+    let before = holiday_snapshot(txn, holiday_id).await?;
+    let am = holiday_entry::ActiveModel {
+        holiday_id: ActiveValue::Set(holiday_id),
+        date: ActiveValue::Set(date),
+        name: ActiveValue::Set(name),
+        fraction: ActiveValue::Set(fraction as f32),
+        r#type: ActiveValue::Set(entry_type.unwrap_or(HolidayEntryType::Public).into()),
+        ..Default::default()
+    };
+    let res = am.insert(txn).await?;
+    record_holiday_change(ctx, holiday_id, before).await?;
+    Ok(res)
+}
+
+/// Set the part of the working day that is off on a holiday, e.g. 0.5 for half a day
+pub async fn holiday_entry_set_fraction(
+    ctx: &Context,
+    db_id: i32,
+    fraction: f64,
+) -> anyhow::Result<holiday_entry::Model> {
+    if !(fraction > 0.0 && fraction <= 1.0) {
+        return Err(anyhow!("Holiday fraction must be in (0, 1], got {}", fraction));
+    }
+    let txn = ctx.txn().await?;
+    let entry = holiday_entry::Entity::find_by_id(db_id)
+        .one(txn)
+        .await?
+        .ok_or(anyhow!("Failed to find a holiday entry with id {}", db_id))?;
+    let before = holiday_snapshot(txn, entry.holiday_id).await?;
+    let am = holiday_entry::ActiveModel {
+        id: ActiveValue::Set(db_id),
+        fraction: ActiveValue::Set(fraction as f32),
+        ..Default::default()
+    };
+    let res = am.update(txn).await?;
+    record_holiday_change(ctx, res.holiday_id, before).await?;
+    Ok(res)
+}
+
+/// Remove a day off from a manually maintained holiday calendar. Returns false if the entry does
+/// not exist.
+pub async fn holiday_entry_delete(ctx: &Context, db_id: i32) -> anyhow::Result<bool> {
+    let txn = ctx.txn().await?;
+    let Some((entry, Some(model))) = holiday_entry::Entity::find_by_id(db_id)
+        .find_also_related(holiday::Entity)
+        .one(txn)
+        .await?
+    else {
+        return Ok(false);
+    };
+    if model.provider != String::from(HolidayProvider::Manual) {
+        return Err(anyhow!("Holiday '{}' is not maintained manually", model.name));
+    }
+    let before = holiday_snapshot(txn, model.id).await?;
+    let res = entry.delete(txn).await?;
+    record_holiday_change(ctx, model.id, before).await?;
+    Ok(res.rows_affected > 0)
+}
+
+/// Record the change of a holiday calendar including its entries, `before` is its snapshot
+/// before the change
+pub async fn record_holiday_change(
+    ctx: &Context,
+    db_id: i32,
+    before: Option<serde_json::Value>,
+) -> anyhow::Result<()> {
+    let after = holiday_snapshot(ctx.txn().await?, db_id).await?;
+    record_change(ctx, AuditEntityType::Holiday, db_id, before.as_ref(), after.as_ref()).await
+}
+
 static COUNTRIES: OnceLock<HashMap<String, String>> = OnceLock::new();
 pub fn countries() -> &'static HashMap<String, String> {
     COUNTRIES.get_or_init(|| {
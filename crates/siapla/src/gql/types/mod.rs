@@ -1,8 +1,18 @@
 pub mod allocation;
+pub mod audit_log;
 pub mod availability;
+pub mod availability_exception;
+pub mod blocked_time;
+pub mod calculation_run;
+pub mod change_set;
 pub mod holiday;
 pub mod issue;
 pub mod plan;
+pub mod project;
+pub mod ramp_up_stage;
 pub mod resource;
+pub mod resource_focus;
 pub mod task;
+pub mod team;
+pub mod user;
 pub mod vacation;
@@ -0,0 +1,260 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use juniper::{GraphQLEnum, graphql_object};
+use sea_orm::prelude::*;
+use sea_orm::{ActiveValue, DatabaseTransaction};
+use strum::{EnumString, IntoStaticStr};
+
+use crate::{
+    app_state::CalculationState,
+    entity::{
+        allocated_resource, allocation, milestone_contention, project, resource_constraint,
+        resource_constraint_entry, task, team_membership,
+    },
+    gql::{common::opt_to_av, context::Context, subscription::GQLCalculationState},
+};
+
+use super::{
+    audit_log::{AuditEntityType, record_change},
+    task::TaskDesignation,
+};
+
+/// Weight of a project's milestones when projects compete for the same resources
+#[derive(GraphQLEnum, IntoStaticStr, EnumString, PartialEq, Eq, Clone, Copy)]
+pub enum ProjectPriority {
+    Low,
+    Medium,
+    High,
+}
+
+impl ProjectPriority {
+    /// Index into the (low, medium, high) cost slopes of the scheduler
+    pub fn slope_index(&self) -> usize {
+        match self {
+            ProjectPriority::Low => 0,
+            ProjectPriority::Medium => 1,
+            ProjectPriority::High => 2,
+        }
+    }
+}
+
+impl From<ProjectPriority> for String {
+    fn from(value: ProjectPriority) -> Self {
+        let s: &'static str = value.into();
+        s.into()
+    }
+}
+
+#[graphql_object]
+#[graphql(name = "Project")]
+impl project::Model {
+    fn db_id(&self) -> &i32 {
+        &self.id
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+    fn priority(&self) -> anyhow::Result<ProjectPriority> {
+        Ok(ProjectPriority::from_str(&self.priority)?)
+    }
+    /// Last modification of the project or one of its tasks
+    fn modified_at(&self) -> &Option<DateTime<Utc>> {
+        &self.modified_at
+    }
+    /// Start of the last calculation whose plan was stored for this project
+    fn calculated_at(&self) -> &Option<DateTime<Utc>> {
+        &self.calculated_at
+    }
+    /// Whether the current plan contains the latest changes of this project
+    fn calculation_state(&self, ctx: &Context) -> GQLCalculationState {
+        let outdated = match (self.modified_at, self.calculated_at) {
+            (_, None) => true,
+            (Some(modified), Some(calculated)) => modified > calculated,
+            (None, Some(_)) => false,
+        };
+        if !outdated {
+            return GQLCalculationState::Finished;
+        }
+        match *ctx.app_state().state_tx.borrow() {
+            CalculationState::Calculating(_) => GQLCalculationState::Calculating,
+            _ => GQLCalculationState::Modified,
+        }
+    }
+    async fn tasks(&self, ctx: &Context) -> anyhow::Result<Vec<task::Model>> {
+        const CIDX: usize = task::Column::ProjectId as usize;
+        let mut tasks = ctx.load_by_col::<task::Entity, CIDX>(self.id).await?;
+        tasks.sort_by(|a, b| a.title.cmp(&b.title));
+        Ok(tasks)
+    }
+    async fn milestones(&self, ctx: &Context) -> anyhow::Result<Vec<task::Model>> {
+        let milestone: &'static str = TaskDesignation::Milestone.into();
+        Ok(self.tasks(ctx).await?.into_iter().filter(|t| t.designation == milestone).collect())
+    }
+    /// Milestones of this project delayed by other projects in the current plan
+    async fn contentions(&self, ctx: &Context) -> anyhow::Result<Vec<milestone_contention::Model>> {
+        let milestone_ids = self.milestones(ctx).await?.into_iter().map(|m| m.id);
+        let res = milestone_contention::Entity::find()
+            .filter(milestone_contention::Column::MilestoneId.is_in(milestone_ids))
+            .all(ctx.txn().await?)
+            .await?;
+        Ok(res)
+    }
+}
+
+#[graphql_object]
+#[graphql(name = "MilestoneContention")]
+impl milestone_contention::Model {
+    /// The delayed milestone
+    async fn milestone(&self, ctx: &Context) -> anyhow::Result<task::Model> {
+        const CIDX: usize = task::Column::Id as usize;
+        let milestone = ctx.load_one_by_col::<task::Entity, CIDX>(self.milestone_id).await?;
+        milestone.ok_or(anyhow!("Failed to find milestone for MilestoneContention"))
+    }
+    /// The project whose tasks use the resources needed by the milestone
+    async fn competing_project(&self, ctx: &Context) -> anyhow::Result<project::Model> {
+        const CIDX: usize = project::Column::Id as usize;
+        let project = ctx.load_one_by_col::<project::Entity, CIDX>(self.project_id).await?;
+        project.ok_or(anyhow!("Failed to find project for MilestoneContention"))
+    }
+    /// Planned date of the milestone, none if it cannot be reached in the planning horizon
+    fn planned_date(&self) -> &Option<DateTime<Utc>> {
+        &self.planned_date
+    }
+    /// Date the milestone would be reached without the competing project
+    fn unaffected_date(&self) -> &DateTime<Utc> {
+        &self.unaffected_date
+    }
+    /// Delay in days caused by the competing project
+    fn delay(&self) -> Option<f64> {
+        self.planned_date
+            .map(|planned| (planned - self.unaffected_date).as_seconds_f64() / (24.0 * 3600.0))
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ProjectSaveInput {
+    db_id: Option<i32>,
+    name: String,
+    description: Option<String>,
+    priority: Option<ProjectPriority>,
+}
+
+pub async fn project_save(
+    ctx: &Context,
+    project: ProjectSaveInput,
+) -> anyhow::Result<project::Model> {
+    let am = project::ActiveModel {
+        id: opt_to_av!(project.db_id),
+        name: ActiveValue::Set(project.name),
+        description: opt_to_av!(project.description),
+        modified_at: ActiveValue::Set(Some(Utc::now())),
+        calculated_at: ActiveValue::NotSet,
+        priority: opt_to_av!(project.priority.map(Into::into)),
+    };
+    let txn = ctx.txn().await?;
+    let before = match am.id {
+        ActiveValue::Set(id) => project::Entity::find_by_id(id).one(txn).await?,
+        _ => None,
+    };
+    let model = if am.id.is_set() { am.update(txn).await? } else { am.insert(txn).await? };
+    record_change(ctx, AuditEntityType::Project, model.id, before.as_ref(), Some(&model)).await?;
+    Ok(model)
+}
+
+/// Mark a project as modified, so its plan is reported as outdated until the next calculation
+pub async fn touch_project(txn: &DatabaseTransaction, project_id: i32) -> anyhow::Result<()> {
+    project::Entity::update_many()
+        .col_expr(project::Column::ModifiedAt, Expr::value(Some(Utc::now())))
+        .filter(project::Column::Id.eq(project_id))
+        .exec(txn)
+        .await?;
+    Ok(())
+}
+
+/// Mark the projects whose plan depends on a resource as modified: projects with tasks the
+/// resource (directly or through a team) may be assigned to or is booked on
+pub async fn touch_resource_projects(
+    txn: &DatabaseTransaction,
+    resource_id: i32,
+) -> anyhow::Result<()> {
+    let team_ids: Vec<i32> = team_membership::Entity::find()
+        .filter(team_membership::Column::ResourceId.eq(resource_id))
+        .all(txn)
+        .await?
+        .into_iter()
+        .map(|m| m.team_id)
+        .collect();
+    let constraint_ids: Vec<i32> = resource_constraint_entry::Entity::find()
+        .filter(resource_constraint_entry::Column::ResourceId.eq(resource_id))
+        .all(txn)
+        .await?
+        .into_iter()
+        .map(|e| e.resource_constraint_id)
+        .collect();
+    let mut task_ids: Vec<i32> = resource_constraint::Entity::find()
+        .filter(
+            resource_constraint::Column::Id
+                .is_in(constraint_ids)
+                .or(resource_constraint::Column::TeamId.is_in(team_ids)),
+        )
+        .all(txn)
+        .await?
+        .into_iter()
+        .map(|c| c.task_id)
+        .collect();
+    let allocation_ids: Vec<i32> = allocated_resource::Entity::find()
+        .filter(allocated_resource::Column::ResourceId.eq(resource_id))
+        .all(txn)
+        .await?
+        .into_iter()
+        .map(|ar| ar.allocation_id)
+        .collect();
+    task_ids.extend(
+        allocation::Entity::find()
+            .filter(allocation::Column::Id.is_in(allocation_ids))
+            .all(txn)
+            .await?
+            .into_iter()
+            .map(|a| a.task_id),
+    );
+    touch_task_projects(txn, task_ids).await
+}
+
+/// Mark the projects with tasks constrained to a team as modified, e.g. when its members change
+pub async fn touch_team_projects(txn: &DatabaseTransaction, team_id: i32) -> anyhow::Result<()> {
+    let task_ids: Vec<i32> = resource_constraint::Entity::find()
+        .filter(resource_constraint::Column::TeamId.eq(team_id))
+        .all(txn)
+        .await?
+        .into_iter()
+        .map(|c| c.task_id)
+        .collect();
+    touch_task_projects(txn, task_ids).await
+}
+
+/// Mark the projects of the given tasks as modified
+pub async fn touch_task_projects(
+    txn: &DatabaseTransaction,
+    task_ids: Vec<i32>,
+) -> anyhow::Result<()> {
+    let project_ids: Vec<i32> = task::Entity::find()
+        .filter(task::Column::Id.is_in(task_ids))
+        .all(txn)
+        .await?
+        .into_iter()
+        .filter_map(|t| t.project_id)
+        .collect();
+    if !project_ids.is_empty() {
+        project::Entity::update_many()
+            .col_expr(project::Column::ModifiedAt, Expr::value(Some(Utc::now())))
+            .filter(project::Column::Id.is_in(project_ids))
+            .exec(txn)
+            .await?;
+    }
+    Ok(())
+}
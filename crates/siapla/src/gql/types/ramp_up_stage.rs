@@ -0,0 +1,78 @@
+use anyhow::anyhow;
+use itertools::Itertools as _;
+use juniper::graphql_object;
+use sea_orm::prelude::*;
+use sea_orm::{ActiveValue, DatabaseTransaction};
+
+use crate::entity::{ramp_up_stage, resource};
+
+/// Slowest allowed onboarding speed, slower stages would stretch tasks beyond any plan
+pub const MIN_RAMP_UP_SPEED: f32 = 0.05;
+
+#[graphql_object]
+#[graphql(name = "RampUpStage")]
+impl ramp_up_stage::Model {
+    fn db_id(&self) -> &i32 {
+        &self.id
+    }
+    /// Number of days after the resource was added until which this stage applies
+    fn days(&self) -> i32 {
+        self.days
+    }
+    /// Speed relative to an experienced resource, e.g. 0.3
+    fn speed(&self) -> f64 {
+        self.speed as f64
+    }
+}
+
+/// Stage of the onboarding profile of a resource. The stage with the lowest `days` not yet
+/// reached applies; afterwards, the resource works at full speed.
+#[derive(juniper::GraphQLInputObject, Debug)]
+pub struct RampUpStageInput {
+    days: i32,
+    speed: f64,
+}
+
+/// Replace the onboarding profile of a resource
+pub async fn update_ramp_up(
+    txn: &DatabaseTransaction,
+    model: &resource::Model,
+    stages: Vec<RampUpStageInput>,
+) -> anyhow::Result<()> {
+    for stage in stages.iter() {
+        if stage.days <= 0 {
+            return Err(anyhow!("Ramp-up stage must last at least one day (got {}).", stage.days));
+        }
+        // checked as stored, tiny speeds become 0.0 as f32
+        if (stage.speed as f32) < MIN_RAMP_UP_SPEED {
+            return Err(anyhow!(
+                "Ramp-up speed must be at least {} (got {}).",
+                MIN_RAMP_UP_SPEED,
+                stage.speed
+            ));
+        }
+    }
+    if !stages.iter().map(|s| s.days).all_unique() {
+        return Err(anyhow!("Ramp-up stages must end on different days."));
+    }
+    ramp_up_stage::Entity::delete_many()
+        .filter(ramp_up_stage::Column::ResourceId.eq(model.id))
+        .exec(txn)
+        .await?;
+    if !stages.is_empty() {
+        ramp_up_stage::Entity::insert_many(
+            stages
+                .into_iter()
+                .map(|stage| ramp_up_stage::ActiveModel {
+                    id: ActiveValue::NotSet,
+                    resource_id: ActiveValue::Set(model.id),
+                    days: ActiveValue::Set(stage.days),
+                    speed: ActiveValue::Set(stage.speed as f32),
+                })
+                .collect::<Vec<_>>(),
+        )
+        .exec(txn)
+        .await?;
+    }
+    Ok(())
+}
@@ -1,21 +1,38 @@
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
 
+use itertools::Itertools as _;
 use juniper::{Nullable, graphql_object};
-use sea_orm::ActiveValue;
 use sea_orm::prelude::*;
+use sea_orm::{ActiveValue, DatabaseTransaction};
 use tracing::error;
 
 use crate::{
-    entity::{availability, holiday, resource, vacation},
+    auth::Role,
+    entity::{
+        availability, availability_exception, availability_pattern, blocked_time, holiday,
+        ramp_up_stage, resource, resource_focus, resource_holiday, team_membership, vacation,
+    },
     gql::{
-        common::{nullable_to_av, opt_to_av},
+        common::{check_version, nullable_to_av, opt_to_av},
         context::Context,
+        dataloader::query_focus_factors,
     },
 };
 
 use super::{
-    availability::{AvailabilityInput, update_availability},
-    holiday::GQLHoliday,
+    audit_log::{AuditEntityType, record_change, resource_snapshot},
+    availability::{
+        AvailabilityInput, AvailabilityPatternInput, update_availability,
+        update_availability_patterns,
+    },
+    availability_exception::AvailabilityExceptionInput,
+    blocked_time::{BlockedTimeInput, update_blocked_times},
+    holiday::{GQLHoliday, SchoolHolidayMode},
+    project::touch_resource_projects,
+    ramp_up_stage::{RampUpStageInput, update_ramp_up},
+    resource_focus::{ResourceFocusInput, update_focus},
     vacation::VacationInput,
 };
 
@@ -35,12 +52,35 @@ impl GQLInterval {
         self.iv.end().value().expect("Must be bounded")
     }
 }
+/// Working time of a resource within a time range, in hours
+pub struct GQLCapacity {
+    pub raw: f64,
+    pub effective: f64,
+}
+
+#[graphql_object]
+#[graphql(name = "Capacity")]
+impl GQLCapacity {
+    /// Combined availability (working time without holidays, vacations, ...)
+    pub fn raw(&self) -> f64 {
+        self.raw
+    }
+    /// Share of the combined availability spent on planned tasks (scaled by the focus factor)
+    pub fn effective(&self) -> f64 {
+        self.effective
+    }
+}
+
 #[graphql_object]
 #[graphql(name = "Resource")]
 impl resource::Model {
     fn db_id(&self) -> &i32 {
         &self.id
     }
+    /// Incremented by every save, pass it to `resourceSave` to detect concurrent changes
+    fn version(&self) -> &i32 {
+        &self.version
+    }
     fn name(&self) -> &str {
         &self.name
     }
@@ -53,17 +93,108 @@ impl resource::Model {
     fn removed(&self) -> &Option<DateTime<Utc>> {
         &self.removed
     }
+    /// Planned hire instead of a real person, allocations to it depend on the hire
+    fn placeholder(&self) -> bool {
+        self.placeholder
+    }
+    /// Expected start of a planned hire, the resource is not available before
+    fn expected_start(&self) -> &Option<DateTime<Utc>> {
+        &self.expected_start
+    }
     pub async fn holiday(&self, ctx: &Context) -> anyhow::Result<Option<GQLHoliday>> {
         const CIDX: usize = holiday::Column::Id as usize;
         let holiday = ctx.load_one_by_col::<holiday::Entity, CIDX>(self.holiday_id).await?;
         Ok(holiday.map(GQLHoliday::from_model))
     }
+    /// Holiday calendars applied in addition to `holiday`, e.g. a company calendar
+    pub async fn additional_holidays(&self, ctx: &Context) -> anyhow::Result<Vec<GQLHoliday>> {
+        const CIDX: usize = resource_holiday::Column::ResourceId as usize;
+        const HCIDX: usize = holiday::Column::Id as usize;
+        let links = ctx.load_by_col::<resource_holiday::Entity, CIDX>(self.id).await?;
+        let mut result = vec![];
+        for link in links {
+            if let Some(holiday) =
+                ctx.load_one_by_col::<holiday::Entity, HCIDX>(link.holiday_id).await?
+            {
+                result.push(GQLHoliday::from_model(holiday));
+            }
+        }
+        Ok(result)
+    }
+    /// Region whose school holidays affect the availability of this resource
+    pub async fn school_holiday(&self, ctx: &Context) -> anyhow::Result<Option<GQLHoliday>> {
+        const CIDX: usize = holiday::Column::Id as usize;
+        let holiday = ctx.load_one_by_col::<holiday::Entity, CIDX>(self.school_holiday_id).await?;
+        Ok(holiday.map(GQLHoliday::from_model))
+    }
+    fn school_holiday_mode(&self) -> anyhow::Result<SchoolHolidayMode> {
+        Ok(SchoolHolidayMode::from_str(&self.school_holiday_mode)?)
+    }
+    /// Default availability, used on all days not covered by an availability pattern
     pub async fn availability(&self, ctx: &Context) -> anyhow::Result<Vec<availability::Model>> {
         const CIDX: usize = availability::Column::ResourceId as usize;
         let availability = ctx.load_by_col::<availability::Entity, CIDX>(self.id).await?;
-        Ok(availability)
+        Ok(availability.into_iter().filter(|a| a.pattern_id.is_none()).collect())
+    }
+    pub async fn availability_patterns(
+        &self,
+        ctx: &Context,
+    ) -> anyhow::Result<Vec<availability_pattern::Model>> {
+        const CIDX: usize = availability_pattern::Column::ResourceId as usize;
+        let mut patterns = ctx.load_by_col::<availability_pattern::Entity, CIDX>(self.id).await?;
+        patterns.sort_by_key(|p| p.valid_from);
+        Ok(patterns)
+    }
+    /// Private, empty unless the user is a planner or the person using this resource
+    pub async fn availability_exceptions(
+        &self,
+        ctx: &Context,
+    ) -> anyhow::Result<Vec<availability_exception::Model>> {
+        const CIDX: usize = availability_exception::Column::ResourceId as usize;
+        if !ctx.may_view_private(self.id) {
+            return Ok(vec![]);
+        }
+        let mut exceptions =
+            ctx.load_by_col::<availability_exception::Entity, CIDX>(self.id).await?;
+        exceptions.sort_by_key(|e| (e.date, e.start));
+        Ok(exceptions)
+    }
+    /// Recurring time not available for tasks, e.g. meetings or support duty
+    pub async fn blocked_times(&self, ctx: &Context) -> anyhow::Result<Vec<blocked_time::Model>> {
+        const CIDX: usize = blocked_time::Column::ResourceId as usize;
+        let mut blocked_times = ctx.load_by_col::<blocked_time::Entity, CIDX>(self.id).await?;
+        blocked_times.sort_by_key(|b| b.id);
+        Ok(blocked_times)
+    }
+    /// Share of the availability spent on planned tasks (date ranged or default)
+    pub async fn focus(&self, ctx: &Context) -> anyhow::Result<Vec<resource_focus::Model>> {
+        const CIDX: usize = resource_focus::Column::ResourceId as usize;
+        let mut focus = ctx.load_by_col::<resource_focus::Entity, CIDX>(self.id).await?;
+        focus.sort_by_key(|f| f.valid_from);
+        Ok(focus)
+    }
+    /// Onboarding profile: reduced speed during the first days after `added`
+    pub async fn ramp_up(&self, ctx: &Context) -> anyhow::Result<Vec<ramp_up_stage::Model>> {
+        const CIDX: usize = ramp_up_stage::Column::ResourceId as usize;
+        let mut stages = ctx.load_by_col::<ramp_up_stage::Entity, CIDX>(self.id).await?;
+        stages.sort_by_key(|s| s.days);
+        Ok(stages)
+    }
+    /// Team memberships of this resource (including past ones)
+    pub async fn team_memberships(
+        &self,
+        ctx: &Context,
+    ) -> anyhow::Result<Vec<team_membership::Model>> {
+        const CIDX: usize = team_membership::Column::ResourceId as usize;
+        let mut memberships = ctx.load_by_col::<team_membership::Entity, CIDX>(self.id).await?;
+        memberships.sort_by_key(|m| m.joined);
+        Ok(memberships)
     }
+    /// Private, empty unless the user is a planner or the person using this resource
     pub async fn vacation(&self, ctx: &Context) -> anyhow::Result<Vec<vacation::Model>> {
+        if !ctx.may_view_private(self.id) {
+            return Ok(vec![]);
+        }
         const CIDX: usize = vacation::Column::ResourceId as usize;
         let vacation = ctx.load_by_col::<vacation::Entity, CIDX>(self.id).await?;
         Ok(vacation)
@@ -90,6 +221,21 @@ impl resource::Model {
             })
             .collect())
     }
+    /// Raw and effective working time between `start` and `end`
+    pub async fn capacity(
+        &self,
+        ctx: &Context,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> anyhow::Result<GQLCapacity> {
+        let ivs =
+            ctx.load_combined_availability(self.id, start.naive_utc(), end.naive_utc()).await?;
+        let focus = query_focus_factors(ctx, &[self.id]).await?;
+        let effective = focus.first().map(|f| f.apply(&ivs)).unwrap_or_else(|| ivs.clone());
+        let hours =
+            |d: Option<chrono::TimeDelta>| d.unwrap_or_default().num_seconds() as f64 / 3600.0;
+        Ok(GQLCapacity { raw: hours(ivs.length()), effective: hours(effective.length()) })
+    }
 }
 
 #[derive(juniper::GraphQLInputObject)]
@@ -100,9 +246,23 @@ pub struct ResourceSaveInput {
     added: DateTime<Utc>,
     removed: Nullable<DateTime<Utc>>,
     holiday_id: Nullable<i32>,
+    school_holiday_id: Nullable<i32>,
+    school_holiday_mode: Option<SchoolHolidayMode>,
+    placeholder: Option<bool>,
+    expected_start: Nullable<DateTime<Utc>>,
+    /// Version of the resource the changes are based on. Saving fails with a `CONFLICT` error if
+    /// the resource was changed since.
+    version: Option<i32>,
+    pub additional_holiday_ids: Option<Vec<i32>>,
     pub availability: Option<Vec<AvailabilityInput>>,
+    pub availability_patterns: Option<Vec<AvailabilityPatternInput>>,
     pub added_vacations: Option<Vec<VacationInput>>,
     pub removed_vacations: Option<Vec<i32>>,
+    pub added_exceptions: Option<Vec<AvailabilityExceptionInput>>,
+    pub removed_exceptions: Option<Vec<i32>>,
+    pub blocked_times: Option<Vec<BlockedTimeInput>>,
+    pub focus: Option<Vec<ResourceFocusInput>>,
+    pub ramp_up: Option<Vec<RampUpStageInput>>,
 }
 
 impl From<ResourceSaveInput> for crate::entity::resource::ActiveModel {
@@ -114,6 +274,11 @@ impl From<ResourceSaveInput> for crate::entity::resource::ActiveModel {
             added: ActiveValue::Set(value.added),
             removed: nullable_to_av!(value.removed),
             holiday_id: nullable_to_av!(value.holiday_id),
+            school_holiday_id: nullable_to_av!(value.school_holiday_id),
+            school_holiday_mode: opt_to_av!(value.school_holiday_mode.map(String::from)),
+            placeholder: opt_to_av!(value.placeholder),
+            expected_start: nullable_to_av!(value.expected_start),
+            version: ActiveValue::NotSet,
         }
     }
 }
@@ -122,12 +287,39 @@ pub async fn resource_save(
     ctx: &Context,
     mut resource: ResourceSaveInput,
 ) -> anyhow::Result<resource::Model> {
-    let availability = resource.availability.take();
+    ctx.require_role(Role::Planner)?;
+    let mut availability = resource.availability.take();
+    let availability_patterns = resource.availability_patterns.take();
     let added_vacations = resource.added_vacations.take().unwrap_or_default();
     let removed_vacations = resource.removed_vacations.take().unwrap_or_default();
-    let am = resource::ActiveModel::from(resource);
+    let added_exceptions = resource.added_exceptions.take().unwrap_or_default();
+    let removed_exceptions = resource.removed_exceptions.take().unwrap_or_default();
+    let additional_holiday_ids = resource.additional_holiday_ids.take();
+    let blocked_times = resource.blocked_times.take();
+    let focus = resource.focus.take();
+    let ramp_up = resource.ramp_up.take();
+    let expected_version = resource.version.take();
+    let mut am = resource::ActiveModel::from(resource);
     let txn = ctx.txn().await?;
-    let model = if am.id.is_set() { am.update(txn).await? } else { am.insert(txn).await? };
+    let (before, before_snapshot) = match am.id {
+        ActiveValue::Set(id) => {
+            (resource::Entity::find_by_id(id).one(txn).await?, resource_snapshot(txn, id).await?)
+        }
+        _ => (None, None),
+    };
+    if let Some(before) = &before {
+        check_version("Resource", before.id, expected_version, before.version)?;
+        am.version = ActiveValue::Set(before.version + 1);
+    }
+    let model = if am.id.is_set() {
+        am.update(txn).await?
+    } else {
+        let model = am.insert(txn).await?;
+        if model.placeholder && availability.is_none() {
+            availability = Some(AvailabilityInput::placeholder_template());
+        }
+        model
+    };
 
     // Handle adding new vacations
     for vacation_input in added_vacations {
@@ -144,10 +336,56 @@ pub async fn resource_save(
             .await?;
     }
 
+    // Handle availability exceptions
+    for exception_input in added_exceptions {
+        let mut exception_am = availability_exception::ActiveModel::try_from(exception_input)?;
+        exception_am.resource_id = ActiveValue::Set(model.id);
+        exception_am.insert(txn).await?;
+    }
+    if !removed_exceptions.is_empty() {
+        availability_exception::Entity::delete_many()
+            .filter(availability_exception::Column::Id.is_in(removed_exceptions))
+            .filter(availability_exception::Column::ResourceId.eq(model.id))
+            .exec(txn)
+            .await?;
+    }
+
+    // Handle additional holiday calendars
+    if let Some(additional_holiday_ids) = additional_holiday_ids {
+        update_additional_holidays(txn, &model, additional_holiday_ids).await?;
+    }
+
+    if let Some(blocked_times) = blocked_times {
+        update_blocked_times(txn, &model, blocked_times).await?;
+    }
+
+    if let Some(focus) = focus {
+        update_focus(txn, &model, focus).await?;
+    }
+    if let Some(ramp_up) = ramp_up {
+        update_ramp_up(txn, &model, ramp_up).await?;
+    }
+
     if let Some(availability) = availability {
-        update_availability(ctx, &model, availability).await?;
+        update_availability(ctx, &model, None, availability).await?;
+    }
+    if let Some(availability_patterns) = availability_patterns {
+        update_availability_patterns(ctx, &model, availability_patterns).await?;
     }
 
+    touch_resource_projects(txn, model.id).await?;
+
+    // logged after all nested data is saved, vacations and availability are part of the entry
+    let after_snapshot = resource_snapshot(txn, model.id).await?;
+    record_change(
+        ctx,
+        AuditEntityType::Resource,
+        model.id,
+        before_snapshot.as_ref(),
+        after_snapshot.as_ref(),
+    )
+    .await?;
+
     // if let Some(successors) = successors {
     //     update_successors(ctx, &model, successors).await?;
     // }
@@ -159,3 +397,35 @@ pub async fn resource_save(
     // TODO: before committing, check if we now have predecessor or parent cycles!
     Ok(model)
 }
+
+async fn update_additional_holidays(
+    txn: &DatabaseTransaction,
+    model: &resource::Model,
+    holiday_ids: Vec<i32>,
+) -> anyhow::Result<()> {
+    let existing = resource_holiday::Entity::find()
+        .filter(resource_holiday::Column::ResourceId.eq(model.id))
+        .all(txn)
+        .await?;
+    let removed: Vec<i32> =
+        existing.iter().filter(|l| !holiday_ids.contains(&l.holiday_id)).map(|l| l.id).collect();
+    if !removed.is_empty() {
+        resource_holiday::Entity::delete_many()
+            .filter(resource_holiday::Column::Id.is_in(removed))
+            .exec(txn)
+            .await?;
+    }
+    for holiday_id in holiday_ids.into_iter().unique() {
+        if existing.iter().any(|l| l.holiday_id == holiday_id) {
+            continue;
+        }
+        resource_holiday::ActiveModel {
+            resource_id: ActiveValue::Set(model.id),
+            holiday_id: ActiveValue::Set(holiday_id),
+            ..Default::default()
+        }
+        .insert(txn)
+        .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use anyhow::anyhow;
+use chrono::NaiveDate;
+use juniper::graphql_object;
+use sea_orm::prelude::*;
+use sea_orm::{ActiveValue, DatabaseTransaction};
+
+use crate::{
+    entity::{resource, resource_focus},
+    gql::common::opt_to_av,
+};
+
+#[graphql_object]
+#[graphql(name = "ResourceFocus")]
+impl resource_focus::Model {
+    fn db_id(&self) -> &i32 {
+        &self.id
+    }
+    /// Share of the availability spent on planned tasks, e.g. 0.7
+    fn factor(&self) -> f64 {
+        self.factor as f64
+    }
+    /// First day this factor applies to (in the resource's timezone)
+    fn valid_from(&self) -> &Option<NaiveDate> {
+        &self.valid_from
+    }
+    /// Last day this factor applies to (in the resource's timezone)
+    fn valid_until(&self) -> &Option<NaiveDate> {
+        &self.valid_until
+    }
+}
+
+/// Focus factor of a resource. Without a date range, it applies to all days not covered by a
+/// date ranged factor. If factors overlap, the one starting last is used.
+#[derive(juniper::GraphQLInputObject, Debug)]
+pub struct ResourceFocusInput {
+    db_id: Option<i32>,
+    factor: f64,
+    valid_from: Option<NaiveDate>,
+    valid_until: Option<NaiveDate>,
+}
+
+/// Replace the focus factors of a resource. Factors not contained in `focus` are removed.
+pub async fn update_focus(
+    txn: &DatabaseTransaction,
+    model: &resource::Model,
+    focus: Vec<ResourceFocusInput>,
+) -> anyhow::Result<()> {
+    let existing: Vec<i32> = resource_focus::Entity::find()
+        .filter(resource_focus::Column::ResourceId.eq(model.id))
+        .all(txn)
+        .await?
+        .into_iter()
+        .map(|f| f.id)
+        .collect();
+    let remove: Vec<i32> = existing
+        .iter()
+        .filter(|id| !focus.iter().any(|f| f.db_id == Some(**id)))
+        .cloned()
+        .collect();
+    if !remove.is_empty() {
+        resource_focus::Entity::delete_many()
+            .filter(resource_focus::Column::Id.is_in(remove))
+            .exec(txn)
+            .await?;
+    }
+    for input in focus {
+        if !(0.0..=1.0).contains(&input.factor) {
+            return Err(anyhow!("Focus factor must be between 0 and 1 (got {}).", input.factor));
+        }
+        if let (Some(from), Some(until)) = (input.valid_from, input.valid_until)
+            && until < from
+        {
+            return Err(anyhow!("Focus factor must end after it starts ({} - {}).", from, until));
+        }
+        if let Some(id) = input.db_id
+            && !existing.contains(&id)
+        {
+            return Err(anyhow!("Focus factor {} does not belong to this resource.", id));
+        }
+        let am = resource_focus::ActiveModel {
+            id: opt_to_av!(input.db_id),
+            resource_id: ActiveValue::Set(model.id),
+            factor: ActiveValue::Set(input.factor as f32),
+            valid_from: ActiveValue::Set(input.valid_from),
+            valid_until: ActiveValue::Set(input.valid_until),
+        };
+        if am.id.is_set() {
+            am.update(txn).await?;
+        } else {
+            am.insert(txn).await?;
+        }
+    }
+    Ok(())
+}
@@ -9,15 +9,23 @@ use strum::{EnumString, IntoStaticStr};
 use tracing::trace;
 
 use crate::{
+    auth::Role,
     entity::{
-        allocation, dependency, resource, resource_constraint, resource_constraint_entry, task,
+        allocation, dependency, project, resource, resource_constraint, resource_constraint_entry,
+        task, team,
     },
     gql::{
-        common::{nullable_to_av, opt_to_av, resolve_many_to_many},
+        common::{check_version, nullable_to_av, opt_to_av, resolve_many_to_many},
         context::Context,
     },
 };
 
+use super::{
+    allocation::AllocationType,
+    audit_log::{AuditEntityType, booking_snapshot, record_change, record_deletes},
+    project::touch_project,
+};
+
 #[derive(GraphQLEnum, IntoStaticStr, EnumString, PartialEq, Eq)]
 pub enum TaskDesignation {
     Task,
@@ -33,12 +41,34 @@ impl From<TaskDesignation> for String {
     }
 }
 
+/// How the planner may distribute a task's effort over the available working time.
+#[derive(GraphQLEnum, IntoStaticStr, EnumString, PartialEq, Eq, Clone, Copy)]
+pub enum SplitPolicy {
+    /// The task must be done in one piece of uninterrupted working time.
+    Contiguous,
+    /// The task may be interrupted at most `maxInterruptions` times.
+    Limited,
+    /// The task may be split freely.
+    Free,
+}
+
+impl From<SplitPolicy> for String {
+    fn from(value: SplitPolicy) -> Self {
+        let s: &'static str = value.into();
+        s.into()
+    }
+}
+
 #[graphql_object]
 #[graphql(name = "Task")]
 impl task::Model {
     fn db_id(&self) -> &i32 {
         &self.id
     }
+    /// Incremented by every save, pass it to `taskSave` to detect concurrent changes
+    fn version(&self) -> &i32 {
+        &self.version
+    }
     fn title(&self) -> &str {
         &self.title
     }
@@ -57,6 +87,12 @@ impl task::Model {
     fn designation(&self) -> anyhow::Result<TaskDesignation> {
         Ok(TaskDesignation::from_str(&self.designation)?)
     }
+    fn split_policy(&self) -> anyhow::Result<SplitPolicy> {
+        Ok(SplitPolicy::from_str(&self.split_policy)?)
+    }
+    fn max_interruptions(&self) -> Option<i32> {
+        self.max_interruptions
+    }
     pub async fn predecessors(&self, ctx: &Context) -> anyhow::Result<Vec<Self>> {
         resolve_many_to_many!(
             ctx,
@@ -98,6 +134,10 @@ impl task::Model {
             }
         }
     }
+    async fn project(&self, ctx: &Context) -> anyhow::Result<Option<project::Model>> {
+        const CIDX: usize = project::Column::Id as usize;
+        ctx.load_one_by_col::<project::Entity, CIDX>(self.project_id).await
+    }
     async fn resource_constraints(
         &self,
         ctx: &Context,
@@ -127,6 +167,11 @@ impl resource_constraint::Model {
     fn speed(&self) -> f64 {
         self.speed as f64
     }
+    /// Team whose current members can fulfill this constraint (in addition to the entries)
+    async fn team(&self, ctx: &Context) -> anyhow::Result<Option<team::Model>> {
+        const CIDX: usize = team::Column::Id as usize;
+        ctx.load_one_by_col::<team::Entity, CIDX>(self.team_id).await
+    }
     async fn entries(
         &self,
         ctx: &Context,
@@ -162,6 +207,7 @@ pub struct ResourceConstraintInput {
     pub optional: bool,
     pub speed: f64,
     pub entries: Vec<ResourceConstraintEntryInput>,
+    pub team_id: Option<i32>,
 }
 
 #[derive(juniper::GraphQLInputObject)]
@@ -174,6 +220,13 @@ pub struct TaskSaveInput {
     earliest_start: Nullable<DateTime<Utc>>,
     schedule_target: Nullable<DateTime<Utc>>,
     effort: Nullable<f64>,
+    split_policy: Option<SplitPolicy>,
+    max_interruptions: Nullable<i32>,
+    /// Project owning the task. New tasks without a project inherit it from their parent.
+    project_id: Nullable<i32>,
+    /// Version of the task the changes are based on. Saving fails with a `CONFLICT` error if
+    /// the task was changed since.
+    version: Option<i32>,
     pub predecessors: Option<Vec<i32>>,
     pub successors: Option<Vec<i32>>,
     pub children: Option<Vec<i32>>,
@@ -191,6 +244,10 @@ impl From<TaskSaveInput> for crate::entity::task::ActiveModel {
             earliest_start: nullable_to_av!(value.earliest_start),
             schedule_target: nullable_to_av!(value.schedule_target),
             effort: nullable_to_av!(value.effort.map(|v| v as f32)),
+            split_policy: opt_to_av!(value.split_policy.map(Into::into)),
+            max_interruptions: nullable_to_av!(value.max_interruptions),
+            project_id: nullable_to_av!(value.project_id),
+            version: ActiveValue::NotSet,
         }
     }
 }
@@ -210,23 +267,29 @@ async fn update_predecessors(
         existing, target, remove, add
     );
     if !remove.is_empty() {
-        dependency::Entity::delete_many()
+        let removed = dependency::Entity::find()
             .filter(
                 dependency::Column::SuccessorId
                     .eq(model.id)
                     .and(dependency::Column::PredecessorId.is_in(remove)),
             )
+            .all(txn)
+            .await?;
+        record_deletes(ctx, AuditEntityType::Dependency, &removed, |d| d.id).await?;
+        dependency::Entity::delete_many()
+            .filter(dependency::Column::Id.is_in(removed.iter().map(|d| d.id)))
             .exec(txn)
             .await?;
     }
-    if !add.is_empty() {
-        dependency::Entity::insert_many(add.into_iter().map(|i| dependency::ActiveModel {
+    for i in add {
+        let am = dependency::ActiveModel {
             predecessor_id: sea_orm::ActiveValue::Set(i),
             successor_id: sea_orm::ActiveValue::Set(model.id),
             ..Default::default()
-        }))
-        .exec(txn)
-        .await?;
+        };
+        let dependency = am.insert(txn).await?;
+        record_change(ctx, AuditEntityType::Dependency, dependency.id, None, Some(&dependency))
+            .await?;
     }
     Ok(())
 }
@@ -246,23 +309,29 @@ async fn update_successors(
         existing, target, remove, add
     );
     if !remove.is_empty() {
-        dependency::Entity::delete_many()
+        let removed = dependency::Entity::find()
             .filter(
                 dependency::Column::PredecessorId
                     .eq(model.id)
                     .and(dependency::Column::SuccessorId.is_in(remove)),
             )
+            .all(txn)
+            .await?;
+        record_deletes(ctx, AuditEntityType::Dependency, &removed, |d| d.id).await?;
+        dependency::Entity::delete_many()
+            .filter(dependency::Column::Id.is_in(removed.iter().map(|d| d.id)))
             .exec(txn)
             .await?;
     }
-    if !add.is_empty() {
-        dependency::Entity::insert_many(add.into_iter().map(|i| dependency::ActiveModel {
+    for i in add {
+        let am = dependency::ActiveModel {
             successor_id: sea_orm::ActiveValue::Set(i),
             predecessor_id: sea_orm::ActiveValue::Set(model.id),
             ..Default::default()
-        }))
-        .exec(txn)
-        .await?;
+        };
+        let dependency = am.insert(txn).await?;
+        record_change(ctx, AuditEntityType::Dependency, dependency.id, None, Some(&dependency))
+            .await?;
     }
     Ok(())
 }
@@ -281,19 +350,19 @@ async fn update_children(
         "children: existing={:?}, target={:?}, remove={:?}, add={:?}",
         existing, target, remove, add
     );
-    if !remove.is_empty() {
-        task::Entity::update_many()
-            .col_expr(task::Column::ParentId, Expr::value(Value::Int(None)))
-            .filter(task::Column::Id.is_in(remove))
-            .exec(txn)
-            .await?;
-    }
-    if !add.is_empty() {
-        task::Entity::update_many()
-            .col_expr(task::Column::ParentId, Expr::value(Value::Int(Some(model.id))))
-            .filter(task::Column::Id.is_in(add))
-            .exec(txn)
-            .await?;
+    for (ids, parent_id) in [(remove, None), (add, Some(model.id))] {
+        if ids.is_empty() {
+            continue;
+        }
+        let tasks = task::Entity::find().filter(task::Column::Id.is_in(ids)).all(txn).await?;
+        for before in tasks {
+            let mut am: task::ActiveModel = before.clone().into();
+            am.parent_id = ActiveValue::Set(parent_id);
+            am.version = ActiveValue::Set(before.version + 1);
+            let after = am.update(txn).await?;
+            record_change(ctx, AuditEntityType::Task, after.id, Some(&before), Some(&after))
+                .await?;
+        }
     }
     Ok(())
 }
@@ -311,28 +380,48 @@ async fn update_resource_constraint_entries(
     let add: HashSet<i32> = target.difference(&existing).cloned().collect();
     // Delete entries not in new_ids
     if !remove.is_empty() {
-        resource_constraint_entry::Entity::delete_many()
+        let removed = resource_constraint_entry::Entity::find()
             .filter(resource_constraint_entry::Column::ResourceId.is_in(remove))
             .filter(resource_constraint_entry::Column::ResourceConstraintId.eq(model.id))
-            .exec(txn)
+            .all(txn)
             .await?;
+        delete_constraint_entries(ctx, &removed).await?;
     }
-    if !add.is_empty() {
-        resource_constraint_entry::Entity::insert_many(
-            add.into_iter()
-                .map(|rid| resource_constraint_entry::ActiveModel {
-                    id: ActiveValue::NotSet,
-                    resource_constraint_id: ActiveValue::Set(model.id),
-                    resource_id: ActiveValue::Set(rid),
-                })
-                .collect::<Vec<_>>(),
-        )
-        .exec(txn)
-        .await?;
+    for rid in add {
+        insert_constraint_entry(ctx, model.id, rid).await?;
     }
     Ok(())
 }
 
+async fn insert_constraint_entry(
+    ctx: &Context,
+    resource_constraint_id: i32,
+    resource_id: i32,
+) -> anyhow::Result<()> {
+    let am = resource_constraint_entry::ActiveModel {
+        id: ActiveValue::NotSet,
+        resource_constraint_id: ActiveValue::Set(resource_constraint_id),
+        resource_id: ActiveValue::Set(resource_id),
+    };
+    let entry = am.insert(ctx.txn().await?).await?;
+    record_change(ctx, AuditEntityType::ResourceConstraintEntry, entry.id, None, Some(&entry)).await
+}
+
+async fn delete_constraint_entries(
+    ctx: &Context,
+    entries: &[resource_constraint_entry::Model],
+) -> anyhow::Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    record_deletes(ctx, AuditEntityType::ResourceConstraintEntry, entries, |e| e.id).await?;
+    resource_constraint_entry::Entity::delete_many()
+        .filter(resource_constraint_entry::Column::Id.is_in(entries.iter().map(|e| e.id)))
+        .exec(ctx.txn().await?)
+        .await?;
+    Ok(())
+}
+
 async fn update_resource_constraints(
     ctx: &Context,
     model: &task::Model,
@@ -357,6 +446,17 @@ async fn update_resource_constraints(
     if num_entries != all_used_resources.len() {
         return Err(anyhow::anyhow!("Each resource can only be used once!"));
     }
+    // team_id has no foreign key (SQLite cannot add them to existing tables)
+    let team_ids: Vec<i32> = constraints.iter().filter_map(|c| c.team_id).unique().collect();
+    if !team_ids.is_empty() {
+        let found = team::Entity::find()
+            .filter(team::Column::Id.is_in(team_ids.clone()))
+            .count(txn)
+            .await?;
+        if found != team_ids.len() as u64 {
+            return Err(anyhow::anyhow!("Unknown team in resource constraints."));
+        }
+    }
 
     // the constraintsare sane, Update existing constraints
     for (i, c) in constraints.iter().take(min_len).enumerate() {
@@ -364,6 +464,7 @@ async fn update_resource_constraints(
         // update columns, only update if changed
         let needs_update = old_c.optional != c.optional
             || old_c.speed != (c.speed as f32)
+            || old_c.team_id != c.team_id
             || old_c.r#type != old_c.r#type; // type is not changed by input, but keep for completeness
         if needs_update {
             let am = resource_constraint::ActiveModel {
@@ -372,8 +473,11 @@ async fn update_resource_constraints(
                 r#type: ActiveValue::Set(old_c.r#type.clone()),
                 optional: ActiveValue::Set(c.optional),
                 speed: ActiveValue::Set(c.speed as f32),
+                team_id: ActiveValue::Set(c.team_id),
             };
-            am.update(txn).await?;
+            let updated = am.update(txn).await?;
+            let entity_type = AuditEntityType::ResourceConstraint;
+            record_change(ctx, entity_type, old_c.id, Some(old_c), Some(&updated)).await?;
         }
         // update relationships
         update_resource_constraint_entries(ctx, old_c, &c.entries).await?;
@@ -387,51 +491,141 @@ async fn update_resource_constraints(
                 r#type: ActiveValue::Set("any".to_string()),
                 optional: ActiveValue::Set(c.optional),
                 speed: ActiveValue::Set(c.speed as f32),
+                team_id: ActiveValue::Set(c.team_id),
             };
             let rc = rc.insert(txn).await?;
-            let entries: Vec<resource_constraint_entry::ActiveModel> = c
-                .entries
-                .iter()
-                .map(|entry| resource_constraint_entry::ActiveModel {
-                    id: ActiveValue::NotSet,
-                    resource_constraint_id: ActiveValue::Set(rc.id),
-                    resource_id: ActiveValue::Set(entry.resource_id),
-                })
-                .collect();
-            if !entries.is_empty() {
-                resource_constraint_entry::Entity::insert_many(entries).exec(txn).await?;
+            record_change(ctx, AuditEntityType::ResourceConstraint, rc.id, None, Some(&rc)).await?;
+            for entry in &c.entries {
+                insert_constraint_entry(ctx, rc.id, entry.resource_id).await?;
             }
         }
     }
     // Remove old constraints if old_len > new_len
     if old_len > new_len {
-        let ids_to_remove: Vec<i32> = old.iter().skip(new_len).map(|c| c.id).collect();
-        if !ids_to_remove.is_empty() {
-            resource_constraint_entry::Entity::delete_many()
-                .filter(
-                    resource_constraint_entry::Column::ResourceConstraintId
-                        .is_in(ids_to_remove.iter().cloned()),
-                )
-                .exec(txn)
-                .await?;
-            resource_constraint::Entity::delete_many()
-                .filter(resource_constraint::Column::Id.is_in(ids_to_remove))
-                .exec(txn)
-                .await?;
-        }
+        delete_constraints(ctx, &old[new_len..]).await?;
+    }
+    Ok(())
+}
+
+async fn delete_constraints(
+    ctx: &Context,
+    constraints: &[resource_constraint::Model],
+) -> anyhow::Result<()> {
+    if constraints.is_empty() {
+        return Ok(());
     }
+    let txn = ctx.txn().await?;
+    let ids: Vec<i32> = constraints.iter().map(|c| c.id).collect();
+    let entries = resource_constraint_entry::Entity::find()
+        .filter(resource_constraint_entry::Column::ResourceConstraintId.is_in(ids.clone()))
+        .all(txn)
+        .await?;
+    delete_constraint_entries(ctx, &entries).await?;
+    record_deletes(ctx, AuditEntityType::ResourceConstraint, constraints, |c| c.id).await?;
+    resource_constraint::Entity::delete_many()
+        .filter(resource_constraint::Column::Id.is_in(ids))
+        .exec(txn)
+        .await?;
     Ok(())
 }
 
+/// Delete tasks and record everything removed with them (dependencies, resource constraints,
+/// bookings and the parent of remaining children), so the deletion can be undone. Returns the
+/// number of deleted tasks.
+pub async fn delete_tasks(ctx: &Context, tasks: Vec<task::Model>) -> anyhow::Result<u64> {
+    let txn = ctx.txn().await?;
+    let ids: Vec<i32> = tasks.iter().map(|t| t.id).collect();
+    let children = task::Entity::find()
+        .filter(task::Column::ParentId.is_in(ids.clone()))
+        .filter(task::Column::Id.is_not_in(ids.clone()))
+        .all(txn)
+        .await?;
+    for before in children {
+        let mut am: task::ActiveModel = before.clone().into();
+        am.parent_id = ActiveValue::Set(None);
+        am.version = ActiveValue::Set(before.version + 1);
+        let after = am.update(txn).await?;
+        record_change(ctx, AuditEntityType::Task, after.id, Some(&before), Some(&after)).await?;
+    }
+    let dependencies = dependency::Entity::find()
+        .filter(
+            dependency::Column::PredecessorId
+                .is_in(ids.clone())
+                .or(dependency::Column::SuccessorId.is_in(ids.clone())),
+        )
+        .all(txn)
+        .await?;
+    record_deletes(ctx, AuditEntityType::Dependency, &dependencies, |d| d.id).await?;
+    let constraints = resource_constraint::Entity::find()
+        .filter(resource_constraint::Column::TaskId.is_in(ids.clone()))
+        .all(txn)
+        .await?;
+    delete_constraints(ctx, &constraints).await?;
+    // planned allocations are recreated by the next calculation, bookings are not
+    let bookings = allocation::Entity::find()
+        .filter(allocation::Column::TaskId.is_in(ids.clone()))
+        .filter(
+            allocation::Column::AllocationType.eq(<&'static str>::from(AllocationType::BOOKING)),
+        )
+        .all(txn)
+        .await?;
+    for booking in bookings {
+        let before = booking_snapshot(txn, booking.id).await?;
+        record_change(ctx, AuditEntityType::Booking, booking.id, before.as_ref(), None).await?;
+    }
+    // children before their parents, undoing restores the parents first
+    let mut remaining = tasks;
+    while !remaining.is_empty() {
+        let parents: HashSet<i32> = remaining.iter().filter_map(|t| t.parent_id).collect();
+        let (leaves, rest): (Vec<_>, Vec<_>) =
+            remaining.into_iter().partition(|t| !parents.contains(&t.id));
+        // hierarchy loops are rejected when saving, but never loop forever
+        let (leaves, rest) = if leaves.is_empty() { (rest, vec![]) } else { (leaves, rest) };
+        record_deletes(ctx, AuditEntityType::Task, &leaves, |t| t.id).await?;
+        remaining = rest;
+    }
+    let res = task::Entity::delete_many().filter(task::Column::Id.is_in(ids)).exec(txn).await?;
+    Ok(res.rows_affected)
+}
+
 pub async fn task_save(ctx: &Context, mut task: TaskSaveInput) -> anyhow::Result<task::Model> {
+    ctx.require_role(Role::Planner)?;
     let predecessors = task.predecessors.take();
     let successors = task.successors.take();
     let children = task.children.take();
     let resource_constraints = task.resource_constraints.take();
+    let expected_version = task.version.take();
     // keep a copy for issue detection after mutations (not used for now)
-    let am = task::ActiveModel::from(task);
+    let mut am = task::ActiveModel::from(task);
     let txn = ctx.txn().await?;
+    // project_id has no foreign key (SQLite cannot add them to existing tables)
+    if let ActiveValue::Set(Some(project_id)) = am.project_id
+        && project::Entity::find_by_id(project_id).one(txn).await?.is_none()
+    {
+        return Err(anyhow!("Unknown project {}", project_id));
+    }
+    let before = match am.id {
+        ActiveValue::Set(id) => task::Entity::find_by_id(id).one(txn).await?,
+        _ => {
+            if let (ActiveValue::NotSet, ActiveValue::Set(Some(parent_id))) =
+                (&am.project_id, &am.parent_id)
+            {
+                let parent = task::Entity::find_by_id(*parent_id).one(txn).await?;
+                am.project_id = ActiveValue::Set(parent.and_then(|p| p.project_id));
+            }
+            None
+        }
+    };
+    if let Some(before) = &before {
+        check_version("Task", before.id, expected_version, before.version)?;
+        am.version = ActiveValue::Set(before.version + 1);
+    }
     let model = if am.id.is_set() { am.update(txn).await? } else { am.insert(txn).await? };
+    let previous_project = before.as_ref().and_then(|t| t.project_id);
+    for project_id in [previous_project, model.project_id].into_iter().flatten().unique() {
+        touch_project(txn, project_id).await?;
+    }
+    record_change(ctx, AuditEntityType::Task, model.id, before.as_ref(), Some(&model)).await?;
 
     if let Some(predecessors) = predecessors {
         update_predecessors(ctx, &model, predecessors).await?;
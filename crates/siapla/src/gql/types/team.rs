@@ -0,0 +1,248 @@
+use std::cmp::{max, min};
+
+use anyhow::anyhow;
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use juniper::graphql_object;
+use sea_orm::prelude::*;
+use sea_orm::{ActiveValue, DatabaseTransaction};
+
+use crate::{
+    entity::{allocated_resource, allocation, resource, team, team_membership},
+    gql::{common::opt_to_av, context::Context, dataloader::query_focus_factors},
+    scheduling::{Interval, Intervals},
+};
+
+use super::{
+    audit_log::{AuditEntityType, record_change, team_snapshot},
+    project::touch_team_projects,
+    resource::GQLCapacity,
+};
+
+#[graphql_object]
+#[graphql(name = "Team")]
+impl team::Model {
+    fn db_id(&self) -> &i32 {
+        &self.id
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+    /// Membership history of this team
+    async fn memberships(&self, ctx: &Context) -> anyhow::Result<Vec<team_membership::Model>> {
+        const CIDX: usize = team_membership::Column::TeamId as usize;
+        let mut memberships = ctx.load_by_col::<team_membership::Entity, CIDX>(self.id).await?;
+        memberships.sort_by_key(|m| m.joined);
+        Ok(memberships)
+    }
+    /// Resources that are currently members of this team
+    async fn members(&self, ctx: &Context) -> anyhow::Result<Vec<resource::Model>> {
+        const CIDX: usize = team_membership::Column::TeamId as usize;
+        const RCIDX: usize = resource::Column::Id as usize;
+        let now = Utc::now();
+        let memberships = ctx.load_by_col::<team_membership::Entity, CIDX>(self.id).await?;
+        let mut result = vec![];
+        for m in memberships {
+            if m.joined > now || m.left.is_some_and(|left| left <= now) {
+                continue;
+            }
+            if let Some(r) = ctx.load_one_by_col::<resource::Entity, RCIDX>(m.resource_id).await?
+                && !result.contains(&r)
+            {
+                result.push(r);
+            }
+        }
+        Ok(result)
+    }
+    /// Combined working time of all members between `start` and `end`, each counted while they
+    /// belong to the team
+    async fn capacity(
+        &self,
+        ctx: &Context,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> anyhow::Result<GQLCapacity> {
+        let mut raw = TimeDelta::zero();
+        let mut effective = TimeDelta::zero();
+        for (rid, ivs) in member_availability(ctx, self.id, start, end).await? {
+            let focus = query_focus_factors(ctx, &[rid]).await?;
+            let focused = focus.first().map(|f| f.apply(&ivs)).unwrap_or_else(|| ivs.clone());
+            raw += ivs.length().unwrap_or_default();
+            effective += focused.length().unwrap_or_default();
+        }
+        Ok(GQLCapacity {
+            raw: raw.num_seconds() as f64 / 3600.0,
+            effective: effective.num_seconds() as f64 / 3600.0,
+        })
+    }
+    /// Hours of planned and booked work of all members between `start` and `end`, each counted
+    /// while they belong to the team
+    async fn load(
+        &self,
+        ctx: &Context,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> anyhow::Result<f64> {
+        let txn = ctx.txn().await?;
+        let mut load = TimeDelta::zero();
+        for (rid, ivs) in member_availability(ctx, self.id, start, end).await? {
+            let allocations = allocated_resource::Entity::find()
+                .find_also_related(allocation::Entity)
+                .filter(allocated_resource::Column::ResourceId.eq(rid))
+                .filter(allocation::Column::Start.lt(end))
+                .filter(allocation::Column::End.gt(start))
+                .all(txn)
+                .await?;
+            let allocated: Intervals<NaiveDateTime> = allocations
+                .into_iter()
+                .filter_map(|(_, a)| a)
+                .filter(|a| a.start < a.end)
+                .map(|a| Interval::new_lcro(a.start.naive_utc(), a.end.naive_utc()))
+                .collect();
+            load += ivs.intersection(&allocated).length().unwrap_or_default();
+        }
+        Ok(load.num_seconds() as f64 / 3600.0)
+    }
+}
+
+#[graphql_object]
+#[graphql(name = "TeamMembership")]
+impl team_membership::Model {
+    fn db_id(&self) -> &i32 {
+        &self.id
+    }
+    async fn team(&self, ctx: &Context) -> anyhow::Result<team::Model> {
+        const CIDX: usize = team::Column::Id as usize;
+        let team = ctx.load_one_by_col::<team::Entity, CIDX>(self.team_id).await?;
+        team.ok_or(anyhow!("Failed to find team for TeamMembership"))
+    }
+    async fn resource(&self, ctx: &Context) -> anyhow::Result<resource::Model> {
+        const CIDX: usize = resource::Column::Id as usize;
+        let resource = ctx.load_one_by_col::<resource::Entity, CIDX>(self.resource_id).await?;
+        resource.ok_or(anyhow!("Failed to find resource for TeamMembership"))
+    }
+    fn joined(&self) -> &DateTime<Utc> {
+        &self.joined
+    }
+    fn left(&self) -> &Option<DateTime<Utc>> {
+        &self.left
+    }
+}
+
+/// Combined availability of each membership of a team, restricted to the time of membership
+async fn member_availability(
+    ctx: &Context,
+    team_id: i32,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> anyhow::Result<Vec<(i32, Intervals<NaiveDateTime>)>> {
+    const CIDX: usize = team_membership::Column::TeamId as usize;
+    let memberships = ctx.load_by_col::<team_membership::Entity, CIDX>(team_id).await?;
+    let mut result = vec![];
+    for m in memberships {
+        let m_start = max(start, m.joined);
+        let m_end = m.left.map_or(end, |left| min(end, left));
+        if m_end <= m_start {
+            continue;
+        }
+        let ivs =
+            ctx.load_combined_availability(m.resource_id, m_start.naive_utc(), m_end.naive_utc());
+        result.push((m.resource_id, ivs.await?));
+    }
+    Ok(result)
+}
+
+/// A resource's membership in a team. Without `left`, the resource is still a member.
+#[derive(juniper::GraphQLInputObject, Debug)]
+pub struct TeamMembershipInput {
+    db_id: Option<i32>,
+    resource_id: i32,
+    joined: DateTime<Utc>,
+    left: Option<DateTime<Utc>>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct TeamSaveInput {
+    db_id: Option<i32>,
+    name: String,
+    description: Option<String>,
+    pub memberships: Option<Vec<TeamMembershipInput>>,
+}
+
+pub async fn team_save(ctx: &Context, mut team: TeamSaveInput) -> anyhow::Result<team::Model> {
+    let memberships = team.memberships.take();
+    let am = team::ActiveModel {
+        id: opt_to_av!(team.db_id),
+        name: ActiveValue::Set(team.name),
+        description: opt_to_av!(team.description),
+    };
+    let txn = ctx.txn().await?;
+    let before = match am.id {
+        ActiveValue::Set(id) => team_snapshot(txn, id).await?,
+        _ => None,
+    };
+    let model = if am.id.is_set() { am.update(txn).await? } else { am.insert(txn).await? };
+    if let Some(memberships) = memberships {
+        update_memberships(txn, &model, memberships).await?;
+        touch_team_projects(txn, model.id).await?;
+    }
+    // logged after the memberships are saved, they are part of the entry
+    let after = team_snapshot(txn, model.id).await?;
+    record_change(ctx, AuditEntityType::Team, model.id, before.as_ref(), after.as_ref()).await?;
+    Ok(model)
+}
+
+/// Replace the membership history of a team. Memberships not contained in `memberships` are
+/// removed.
+async fn update_memberships(
+    txn: &DatabaseTransaction,
+    model: &team::Model,
+    memberships: Vec<TeamMembershipInput>,
+) -> anyhow::Result<()> {
+    let existing: Vec<i32> = team_membership::Entity::find()
+        .filter(team_membership::Column::TeamId.eq(model.id))
+        .all(txn)
+        .await?
+        .into_iter()
+        .map(|m| m.id)
+        .collect();
+    let remove: Vec<i32> = existing
+        .iter()
+        .filter(|id| !memberships.iter().any(|m| m.db_id == Some(**id)))
+        .cloned()
+        .collect();
+    if !remove.is_empty() {
+        team_membership::Entity::delete_many()
+            .filter(team_membership::Column::Id.is_in(remove))
+            .exec(txn)
+            .await?;
+    }
+    for membership in memberships {
+        if membership.left.is_some_and(|left| left <= membership.joined) {
+            return Err(anyhow!(
+                "Team membership of resource {} must end after it starts.",
+                membership.resource_id
+            ));
+        }
+        if let Some(id) = membership.db_id
+            && !existing.contains(&id)
+        {
+            return Err(anyhow!("Team membership {} does not belong to this team.", id));
+        }
+        let am = team_membership::ActiveModel {
+            id: opt_to_av!(membership.db_id),
+            team_id: ActiveValue::Set(model.id),
+            resource_id: ActiveValue::Set(membership.resource_id),
+            joined: ActiveValue::Set(membership.joined),
+            left: ActiveValue::Set(membership.left),
+        };
+        if am.id.is_set() {
+            am.update(txn).await?;
+        } else {
+            am.insert(txn).await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,223 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use juniper::{Nullable, graphql_object};
+use sea_orm::prelude::*;
+use sea_orm::{ActiveValue, DatabaseTransaction, QueryOrder as _};
+
+use crate::{
+    auth::{Role, TokenKind, create_token, hash_password},
+    entity::{auth_token, resource, user},
+    gql::{
+        audit_log::{AuditEntityType, record_change},
+        common::{nullable_to_av, opt_to_av},
+        context::Context,
+    },
+};
+
+#[graphql_object]
+#[graphql(name = "User")]
+impl user::Model {
+    fn db_id(&self) -> &i32 {
+        &self.id
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn created_at(&self) -> &DateTime<Utc> {
+        &self.created_at
+    }
+    fn role(&self) -> anyhow::Result<Role> {
+        Ok(Role::from_str(&self.role)?)
+    }
+    /// The resource representing the person using this account
+    async fn resource(&self, ctx: &Context) -> anyhow::Result<Option<resource::Model>> {
+        const CIDX: usize = resource::Column::Id as usize;
+        ctx.load_one_by_col::<resource::Entity, CIDX>(self.resource_id).await
+    }
+}
+
+#[graphql_object]
+#[graphql(name = "ApiToken")]
+impl auth_token::Model {
+    fn db_id(&self) -> &i32 {
+        &self.id
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn kind(&self) -> anyhow::Result<TokenKind> {
+        Ok(TokenKind::from_str(&self.kind)?)
+    }
+    fn created_at(&self) -> &DateTime<Utc> {
+        &self.created_at
+    }
+    fn expires_at(&self) -> &Option<DateTime<Utc>> {
+        &self.expires_at
+    }
+    fn last_used_at(&self) -> &Option<DateTime<Utc>> {
+        &self.last_used_at
+    }
+}
+
+/// A newly created API token. The token cannot be retrieved again later.
+pub struct GQLCreatedApiToken {
+    pub token: String,
+    pub api_token: auth_token::Model,
+}
+
+#[graphql_object]
+#[graphql(name = "CreatedApiToken", context = Context)]
+impl GQLCreatedApiToken {
+    /// Secret to send as `Authorization: Bearer <token>`
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+    pub fn api_token(&self) -> &auth_token::Model {
+        &self.api_token
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct UserSaveInput {
+    db_id: Option<i32>,
+    name: String,
+    /// Required for new users, keeps the current password if not given
+    password: Option<String>,
+    /// Defaults to `VIEWER` for new users, only admins may change roles
+    role: Option<Role>,
+    /// Only admins may change the resource of a user
+    resource_id: Nullable<i32>,
+}
+
+pub async fn user_save(ctx: &Context, mut user: UserSaveInput) -> anyhow::Result<user::Model> {
+    let txn = ctx.txn().await?;
+    // without accounts everybody would be admin, the first one is created on the command line
+    let current = ctx.user().ok_or(anyhow!(
+        "Not logged in, create the first user with `siapla-serve --create-admin <name>`"
+    ))?;
+    // users may change their own name and password, everything else is up to admins
+    let own_account = user.db_id == Some(current.id);
+    if !own_account || user.role.is_some() || !user.resource_id.is_implicit_null() {
+        ctx.require_role(Role::Admin)?;
+    }
+    if user.db_id.is_none() && user.role.is_none() {
+        user.role = Some(Role::Viewer);
+    }
+    if let Nullable::Some(resource_id) = user.resource_id
+        && resource::Entity::find_by_id(resource_id).one(txn).await?.is_none()
+    {
+        // resource_id has no foreign key (SQLite cannot add them to existing tables)
+        return Err(anyhow!("Unknown resource {}", resource_id));
+    }
+    if user.name.trim().is_empty() {
+        return Err(anyhow!("User name must not be empty"));
+    }
+    if user.db_id.is_none() && user.password.is_none() {
+        return Err(anyhow!("New users need a password"));
+    }
+    let password_hash = user.password.as_deref().map(hash_password).transpose()?;
+    let am = user::ActiveModel {
+        id: opt_to_av!(user.db_id),
+        name: ActiveValue::Set(user.name.trim().to_owned()),
+        password_hash: opt_to_av!(password_hash),
+        created_at: if user.db_id.is_some() {
+            ActiveValue::NotSet
+        } else {
+            ActiveValue::Set(Utc::now())
+        },
+        role: opt_to_av!(user.role.map(Into::into)),
+        resource_id: nullable_to_av!(user.resource_id),
+    };
+    let before = match am.id {
+        ActiveValue::Set(id) => user::Entity::find_by_id(id).one(txn).await?,
+        _ => None,
+    };
+    let model = if am.id.is_set() { am.update(txn).await? } else { am.insert(txn).await? };
+    ensure_admin_exists(txn).await?;
+    record_change(ctx, AuditEntityType::User, model.id, before.as_ref(), Some(&model)).await?;
+    if user.password.is_some() {
+        // a new password ends all sessions of the user
+        auth_token::Entity::delete_many()
+            .filter(auth_token::Column::UserId.eq(model.id))
+            .filter(auth_token::Column::Kind.eq(<&'static str>::from(TokenKind::Session)))
+            .exec(txn)
+            .await?;
+    }
+    Ok(model)
+}
+
+pub async fn user_delete(ctx: &Context, db_id: i32) -> anyhow::Result<bool> {
+    ctx.require_role(Role::Admin)?;
+    let txn = ctx.txn().await?;
+    if user::Entity::find().filter(user::Column::Id.ne(db_id)).count(txn).await? == 0 {
+        return Err(anyhow!("The last user cannot be deleted, this would disable authentication"));
+    }
+    let Some(before) = user::Entity::find_by_id(db_id).one(txn).await? else {
+        return Ok(false);
+    };
+    // checked before deleting, without an admin nobody could manage users and settings anymore
+    let admin: &'static str = Role::Admin.into();
+    let other_admins = user::Entity::find()
+        .filter(user::Column::Id.ne(db_id))
+        .filter(user::Column::Role.eq(admin))
+        .count(txn)
+        .await?;
+    if before.role == admin && other_admins == 0 {
+        return Err(anyhow!("At least one user must keep the Admin role"));
+    }
+    // recorded first, the entries of the deleted user keep only the user name
+    record_change(ctx, AuditEntityType::User, db_id, Some(&before), None).await?;
+    before.delete(txn).await?;
+    Ok(true)
+}
+
+/// Without an admin, nobody could manage users and settings anymore
+async fn ensure_admin_exists(txn: &DatabaseTransaction) -> anyhow::Result<()> {
+    let admins = user::Entity::find()
+        .filter(user::Column::Role.eq(<&'static str>::from(Role::Admin)))
+        .count(txn)
+        .await?;
+    if admins == 0 {
+        return Err(anyhow!("At least one user must keep the Admin role"));
+    }
+    Ok(())
+}
+
+/// API tokens of the current user
+pub async fn api_tokens(ctx: &Context) -> anyhow::Result<Vec<auth_token::Model>> {
+    let user = ctx.user().ok_or(anyhow!("Not logged in"))?;
+    let res = auth_token::Entity::find()
+        .filter(auth_token::Column::UserId.eq(user.id))
+        .filter(auth_token::Column::Kind.eq(<&'static str>::from(TokenKind::Api)))
+        .order_by_asc(auth_token::Column::Name)
+        .all(ctx.txn().await?)
+        .await?;
+    Ok(res)
+}
+
+pub async fn api_token_create(
+    ctx: &Context,
+    name: String,
+    expires_at: Option<DateTime<Utc>>,
+) -> anyhow::Result<GQLCreatedApiToken> {
+    let user = ctx.user().ok_or(anyhow!("Not logged in"))?;
+    if expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+        return Err(anyhow!("API tokens must expire in the future"));
+    }
+    let (token, api_token) =
+        create_token(ctx.txn().await?, user, TokenKind::Api, name, expires_at).await?;
+    Ok(GQLCreatedApiToken { token, api_token })
+}
+
+/// Revoke an API token of the current user
+pub async fn api_token_delete(ctx: &Context, db_id: i32) -> anyhow::Result<bool> {
+    let user = ctx.user().ok_or(anyhow!("Not logged in"))?;
+    let res = auth_token::Entity::delete_many()
+        .filter(auth_token::Column::Id.eq(db_id))
+        .filter(auth_token::Column::UserId.eq(user.id))
+        .exec(ctx.txn().await?)
+        .await?;
+    Ok(res.rows_affected > 0)
+}
@@ -1,7 +1,28 @@
+use std::str::FromStr;
+
 use crate::entity::vacation;
 use chrono::{DateTime, Utc};
-use juniper::graphql_object;
+use juniper::{GraphQLEnum, graphql_object};
 use sea_orm::ActiveValue;
+use strum::{EnumString, IntoStaticStr};
+
+/// Part of each working day within a vacation that is taken off
+#[derive(GraphQLEnum, IntoStaticStr, EnumString, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum VacationPortion {
+    /// The whole time between `from` and `until`
+    Full,
+    /// The first half of the working time of each day
+    Morning,
+    /// The second half of the working time of each day
+    Afternoon,
+}
+
+impl From<VacationPortion> for String {
+    fn from(value: VacationPortion) -> Self {
+        let s: &'static str = value.into();
+        s.into()
+    }
+}
 
 #[graphql_object]
 #[graphql(name = "Vacation")]
@@ -15,12 +36,16 @@ impl vacation::Model {
     fn until(&self) -> DateTime<Utc> {
         self.until
     }
+    fn portion(&self) -> anyhow::Result<VacationPortion> {
+        Ok(VacationPortion::from_str(&self.portion)?)
+    }
 }
 
 #[derive(juniper::GraphQLInputObject)]
 pub struct VacationInput {
     from: DateTime<Utc>,
     until: DateTime<Utc>,
+    portion: Option<VacationPortion>,
 }
 
 impl From<VacationInput> for crate::entity::vacation::ActiveModel {
@@ -30,6 +55,7 @@ impl From<VacationInput> for crate::entity::vacation::ActiveModel {
             resource_id: ActiveValue::NotSet,
             from: ActiveValue::Set(value.from),
             until: ActiveValue::Set(value.until),
+            portion: ActiveValue::Set(value.portion.unwrap_or(VacationPortion::Full).into()),
         }
     }
 }
@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+
+use itertools::Itertools as _;
+use sea_orm::{
+    ActiveValue, ColumnTrait, ConnectionTrait, EntityTrait, Order, QueryFilter, QueryOrder,
+};
+use siapla_open_holidays_api::models::{LocalizedText, SubdivisionResponse};
+use tracing::{info, warn};
+
+use crate::{entity::subdivision, gql::holiday::countries};
+
+use super::open_holidays;
+
+/// Catalogue file format: the subdivisions of each country as returned by the OpenHolidays API,
+/// keyed by the country's isocode.
+pub type Catalogue = BTreeMap<String, Vec<SubdivisionResponse>>;
+
+/// Subdivisions of a country, from the database. Countries that are not yet part of the
+/// catalogue are downloaded once and stored (countries without any subdivisions are looked up
+/// again each time).
+pub async fn subdivisions<C: ConnectionTrait>(
+    db: &C,
+    country_isocode: &str,
+) -> anyhow::Result<Vec<subdivision::Model>> {
+    let stored = query_subdivisions(db, country_isocode).await?;
+    if !stored.is_empty() {
+        return Ok(stored);
+    }
+    let downloaded = open_holidays::subdivisions(country_isocode).await?;
+    store(db, country_isocode, downloaded).await?;
+    query_subdivisions(db, country_isocode).await
+}
+
+/// Subdivisions downloaded by `download`
+pub struct Download {
+    pub catalogue: Catalogue,
+    /// Countries whose download failed, they are not part of `catalogue`
+    pub failed: Vec<String>,
+}
+
+/// Download the subdivisions of all supported countries. Countries whose download fails are
+/// skipped, so the stored subdivisions of these countries can be kept.
+pub async fn download() -> Download {
+    let mut catalogue = Catalogue::new();
+    let mut failed = vec![];
+    for country_isocode in countries().keys().sorted() {
+        match open_holidays::subdivisions(country_isocode).await {
+            Ok(downloaded) => {
+                info!("Downloaded {} subdivisions of {}", downloaded.len(), country_isocode);
+                catalogue.insert(country_isocode.clone(), downloaded);
+            }
+            Err(err) => {
+                warn!("Failed to download the subdivisions of {}: {}", country_isocode, err);
+                failed.push(country_isocode.clone());
+            }
+        }
+    }
+    Download { catalogue, failed }
+}
+
+/// Store a catalogue. Countries contained in it replace the stored ones, all others are kept.
+/// Returns the number of stored subdivisions.
+pub async fn store_catalogue<C: ConnectionTrait>(
+    db: &C,
+    catalogue: Catalogue,
+) -> anyhow::Result<usize> {
+    let mut count = 0;
+    for (country_isocode, subdivisions) in catalogue {
+        count += store(db, &country_isocode, subdivisions).await?;
+    }
+    Ok(count)
+}
+
+/// Import a catalogue file (see `Catalogue`). Countries contained in the file replace the stored
+/// ones, all others are kept. Returns the number of stored subdivisions.
+pub async fn import<C: ConnectionTrait>(db: &C, content: &str) -> anyhow::Result<usize> {
+    let catalogue: Catalogue = serde_json::from_str(content)?;
+    if let Some(unknown) = catalogue.keys().find(|isocode| !countries().contains_key(*isocode)) {
+        return Err(anyhow::anyhow!("Unknown country code: {}", unknown));
+    }
+    store_catalogue(db, catalogue).await
+}
+
+/// Export the stored catalogue, e.g. to import it on a machine without network access
+pub async fn export<C: ConnectionTrait>(db: &C) -> anyhow::Result<String> {
+    let mut catalogue = Catalogue::new();
+    let stored =
+        subdivision::Entity::find().order_by(subdivision::Column::Id, Order::Asc).all(db).await?;
+    for sd in stored {
+        let mut response = SubdivisionResponse::new(
+            vec![],
+            sd.code,
+            vec![LocalizedText::new("EN".into(), sd.name)],
+            vec![],
+            sd.short_name,
+        );
+        response.iso_code = Some(sd.iso_code);
+        catalogue.entry(sd.country_isocode).or_default().push(response);
+    }
+    Ok(serde_json::to_string_pretty(&catalogue)?)
+}
+
+async fn query_subdivisions<C: ConnectionTrait>(
+    db: &C,
+    country_isocode: &str,
+) -> anyhow::Result<Vec<subdivision::Model>> {
+    Ok(subdivision::Entity::find()
+        .filter(subdivision::Column::CountryIsocode.eq(country_isocode))
+        .order_by(subdivision::Column::Id, Order::Asc)
+        .all(db)
+        .await?)
+}
+
+async fn store<C: ConnectionTrait>(
+    db: &C,
+    country_isocode: &str,
+    subdivisions: Vec<SubdivisionResponse>,
+) -> anyhow::Result<usize> {
+    subdivision::Entity::delete_many()
+        .filter(subdivision::Column::CountryIsocode.eq(country_isocode))
+        .exec(db)
+        .await?;
+    let new_subdivisions = subdivisions
+        .into_iter()
+        .map(|sd| subdivision::ActiveModel {
+            country_isocode: ActiveValue::Set(country_isocode.to_string()),
+            code: ActiveValue::Set(sd.code),
+            iso_code: ActiveValue::Set(sd.iso_code.flatten()),
+            name: ActiveValue::Set(sd.name.first().map(|n| n.text.clone()).unwrap_or_default()),
+            short_name: ActiveValue::Set(sd.short_name),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+    let count = new_subdivisions.len();
+    if !new_subdivisions.is_empty() {
+        subdivision::Entity::insert_many(new_subdivisions).exec(db).await?;
+    }
+    Ok(count)
+}
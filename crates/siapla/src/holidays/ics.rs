@@ -0,0 +1,118 @@
+use anyhow::anyhow;
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+
+use super::ProvidedEntry;
+
+/// Parse the events of an iCalendar (`.ics`) file into one entry per day.
+/// Recurrence rules are not expanded, holiday calendars usually list every occurrence.
+pub fn parse_ics(content: &str) -> anyhow::Result<Vec<ProvidedEntry>> {
+    // unfold continuation lines (starting with a space or tab)
+    let mut lines: Vec<String> = vec![];
+    for line in content.lines() {
+        match line.strip_prefix([' ', '\t']) {
+            Some(rest) if !lines.is_empty() => {
+                lines.last_mut().expect("checked above").push_str(rest)
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+    if !lines.first().is_some_and(|l| l.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return Err(anyhow!("Not an iCalendar file"));
+    }
+
+    let mut result = vec![];
+    let mut event: Option<(Option<_>, Option<_>, String)> = None;
+    for line in lines {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = key.split_once(';').map_or(key, |(name, _)| name);
+        match (name.to_ascii_uppercase().as_str(), event.as_mut()) {
+            ("BEGIN", _) if value.trim().eq_ignore_ascii_case("VEVENT") => {
+                event = Some((None, None, String::new()))
+            }
+            ("END", Some((start, end, summary))) if value.trim().eq_ignore_ascii_case("VEVENT") => {
+                let start: (NaiveDate, bool) =
+                    start.ok_or(anyhow!("Event '{}' has no DTSTART", summary))?;
+                let last = match *end {
+                    // a date-only or midnight end is exclusive
+                    Some((end, true)) if end > start.0 => end - TimeDelta::days(1),
+                    Some((end, false)) => end,
+                    _ => start.0,
+                };
+                for date in start.0.iter_days().take_while(|d| *d <= last) {
+                    result.push(ProvidedEntry { date, name: summary.clone() });
+                }
+                event = None;
+            }
+            ("DTSTART", Some((start, _, _))) => *start = Some(parse_date(value)?),
+            ("DTEND", Some((_, end, _))) => *end = Some(parse_date(value)?),
+            ("SUMMARY", Some((_, _, summary))) => *summary = unescape(value),
+            _ => {}
+        }
+    }
+    result.sort_by_key(|e| e.date);
+    Ok(result)
+}
+
+/// Parse a DATE or DATE-TIME value. The flag is true if the value falls on midnight.
+fn parse_date(value: &str) -> anyhow::Result<(NaiveDate, bool)> {
+    let value = value.trim();
+    if !value.contains('T') {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d")
+            .map_err(|e| anyhow!("Invalid date '{}': {}", value, e))?;
+        return Ok((date, true));
+    }
+    let datetime = NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S")
+        .map_err(|e| anyhow!("Invalid date-time '{}': {}", value, e))?;
+    Ok((datetime.date(), datetime.time() == chrono::NaiveTime::MIN))
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => result.push(' '),
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ics() {
+        let content = "BEGIN:VCALENDAR\r\n\
+            VERSION:2.0\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;VALUE=DATE:20251225\r\n\
+            DTEND;VALUE=DATE:20251227\r\n\
+            SUMMARY:Christmas\\, and Boxing\r\n  Day\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART:20251003T000000Z\r\n\
+            SUMMARY:Unity Day\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+        let entries = parse_ics(content).unwrap();
+        let dates: Vec<_> = entries.iter().map(|e| e.date.to_string()).collect();
+        assert_eq!(dates, vec!["2025-10-03", "2025-12-25", "2025-12-26"]);
+        assert_eq!(entries[1].name, "Christmas, and Boxing Day");
+        assert_eq!(entries[0].name, "Unity Day");
+    }
+
+    #[test]
+    fn test_parse_ics_invalid() {
+        assert!(parse_ics("SUMMARY:nothing").is_err());
+        assert!(parse_ics("BEGIN:VCALENDAR\nBEGIN:VEVENT\nSUMMARY:x\nEND:VEVENT\n").is_err());
+    }
+}
@@ -0,0 +1,78 @@
+pub mod catalogue;
+mod ics;
+mod open_holidays;
+
+use std::sync::OnceLock;
+
+use chrono::{NaiveDate, TimeDelta, Utc};
+use juniper::GraphQLEnum;
+use strum::{EnumString, IntoStaticStr};
+
+pub use ics::parse_ics;
+pub use open_holidays::{DEFAULT_OPEN_HOLIDAYS_URL, set_global_open_holidays_url};
+
+use crate::{entity::holiday, gql::holiday::HolidayEntryType};
+
+pub const DEFAULT_HOLIDAY_MAX_AGE_DAYS: i64 = 30;
+
+// global staleness policy that can be set from the server's command line
+static HOLIDAY_MAX_AGE: OnceLock<TimeDelta> = OnceLock::new();
+
+/// Set the age after which downloaded holiday entries are refreshed. Call early in program
+/// startup.
+pub fn set_global_holiday_max_age(max_age: TimeDelta) {
+    let _ = HOLIDAY_MAX_AGE.set(max_age);
+}
+
+/// Whether the downloaded entries of `holiday` should be refreshed
+pub fn is_stale(holiday: &holiday::Model) -> bool {
+    let max_age =
+        HOLIDAY_MAX_AGE.get().copied().unwrap_or(TimeDelta::days(DEFAULT_HOLIDAY_MAX_AGE_DAYS));
+    holiday.provider == String::from(HolidayProvider::OpenHolidays)
+        && holiday.refreshed_at.is_some_and(|refreshed_at| Utc::now() - refreshed_at > max_age)
+}
+
+/// Single day off as delivered by a holiday provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvidedEntry {
+    pub date: NaiveDate,
+    pub name: String,
+}
+
+/// Source of the entries of a holiday calendar
+#[derive(GraphQLEnum, IntoStaticStr, EnumString, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum HolidayProvider {
+    /// Downloaded on demand from the OpenHolidays API
+    OpenHolidays,
+    /// Imported once from an iCalendar file
+    Ics,
+    /// Entered by hand
+    Manual,
+}
+
+impl From<HolidayProvider> for String {
+    fn from(value: HolidayProvider) -> Self {
+        let s: &'static str = value.into();
+        s.into()
+    }
+}
+
+impl HolidayProvider {
+    /// Entries of `holiday` between `from` and `until` (inclusive) that are not yet stored.
+    /// Only the OpenHolidays provider downloads entries on demand, the entries of all other
+    /// providers are stored when they are imported or entered.
+    pub async fn fetch_entries(
+        self,
+        holiday: &holiday::Model,
+        entry_type: HolidayEntryType,
+        from: NaiveDate,
+        until: NaiveDate,
+    ) -> anyhow::Result<Vec<ProvidedEntry>> {
+        match self {
+            HolidayProvider::OpenHolidays => {
+                open_holidays::fetch_entries(holiday, entry_type, from, until).await
+            }
+            HolidayProvider::Ics | HolidayProvider::Manual => Ok(vec![]),
+        }
+    }
+}
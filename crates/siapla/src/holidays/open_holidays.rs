@@ -0,0 +1,86 @@
+use std::sync::OnceLock;
+
+use siapla_open_holidays_api::apis::{configuration::Configuration, holidays_api, regional_api};
+use siapla_open_holidays_api::models::SubdivisionResponse;
+
+use crate::{entity::holiday, gql::holiday::HolidayEntryType};
+
+use super::ProvidedEntry;
+
+pub const DEFAULT_OPEN_HOLIDAYS_URL: &str = "https://openholidaysapi.org";
+
+// global base url of the OpenHolidays API that can be set from the server's command line
+static OPEN_HOLIDAYS_URL: OnceLock<String> = OnceLock::new();
+
+/// Set the base url of the OpenHolidays API (or a local stand-in). Call early in program startup.
+pub fn set_global_open_holidays_url(url: impl Into<String>) {
+    let _ = OPEN_HOLIDAYS_URL.set(url.into().trim_end_matches('/').to_string());
+}
+
+fn configuration() -> Configuration {
+    let base_path =
+        OPEN_HOLIDAYS_URL.get().map(String::as_str).unwrap_or(DEFAULT_OPEN_HOLIDAYS_URL);
+    Configuration { base_path: base_path.into(), ..Default::default() }
+}
+
+/// Subdivisions (regions) of a country
+pub async fn subdivisions(country_isocode: &str) -> anyhow::Result<Vec<SubdivisionResponse>> {
+    Ok(regional_api::subdivisions_get(&configuration(), country_isocode, "EN".into()).await?)
+}
+
+/// Download the entries of `holiday` (identified by its country / region isocode)
+pub async fn fetch_entries(
+    holiday: &holiday::Model,
+    entry_type: HolidayEntryType,
+    from: chrono::NaiveDate,
+    until: chrono::NaiveDate,
+) -> anyhow::Result<Vec<ProvidedEntry>> {
+    // https://openholidaysapi.org/PublicHolidays?countryIsoCode=DE&languageIsoCode=EN&validFrom=2025-01-01&validTo=2025-12-31
+    let config = configuration();
+    let isocode = holiday.external_id.as_str();
+    let entries = match entry_type {
+        HolidayEntryType::Public => {
+            holidays_api::public_holidays_get(
+                &config,
+                &isocode[0..2],
+                from,
+                until,
+                Some(&holiday.language),
+                Some(isocode),
+            )
+            .await?
+        }
+        HolidayEntryType::School => {
+            holidays_api::school_holidays_get(
+                &config,
+                &isocode[0..2],
+                from,
+                until,
+                Some(&holiday.language),
+                Some(isocode),
+            )
+            .await?
+        }
+    };
+
+    Ok(entries
+        .iter()
+        .filter(|e| e.nationwide || e.subdivisions.iter().flatten().any(|d| d.code == isocode))
+        .flat_map(|e| {
+            // not every holiday is translated to every language
+            let name = e
+                .name
+                .iter()
+                .find(|n| n.language.eq_ignore_ascii_case(&holiday.language))
+                .or(e.name.first())
+                .map(|n| n.text.clone())
+                .unwrap_or("".into());
+            // school holidays may reach outside of the requested range
+            e.start_date
+                .max(from)
+                .iter_days()
+                .take_while(move |date| *date <= e.end_date.min(until))
+                .map(move |date| ProvidedEntry { date, name: name.clone() })
+        })
+        .collect())
+}
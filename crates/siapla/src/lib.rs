@@ -3,8 +3,10 @@ use std::fmt::Display;
 use thiserror::Error;
 
 pub mod app_state;
+pub mod auth;
 pub mod entity;
 pub mod gql;
+pub mod holidays;
 pub mod scheduling;
 
 #[derive(Error, Debug)]
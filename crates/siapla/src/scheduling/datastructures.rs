@@ -31,8 +31,10 @@ pub struct ProjectObjects {
 pub struct Task {
     pub parent: Option<Weak<RefCell<Group>>>,
     pub db_id: i32,
+    pub project_id: Option<i32>,
     pub title: String,
     pub effort: f64,
+    pub split_policy: SplitPolicy,
     pub constraints: Vec<ResourceConstraint>,
     // booking-related metadata (filled during query_problem)
     pub booked_until: Option<NaiveDateTime>,
@@ -43,6 +45,13 @@ pub struct Task {
     pub booked_final: bool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitPolicy {
+    Contiguous,
+    MaxInterruptions(usize),
+    Free,
+}
+
 #[derive(Debug, Clone)]
 pub struct Requirement {
     pub db_id: i32,
@@ -53,8 +62,11 @@ pub struct Requirement {
 #[derive(Debug, Clone)]
 pub struct Milestone {
     pub db_id: i32,
+    pub project_id: Option<i32>,
     pub title: String,
     pub schedule_target: NaiveDateTime,
+    // index into the (low, medium, high) cost slopes, from the project's priority
+    pub priority: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -84,8 +96,20 @@ pub struct Resource {
     pub name: String,
     pub timezone: String,
     pub slots: Vec<Slot>,
+    // regular working time (weekly pattern without holidays / vacations), used to detect
+    // interruptions of tasks
+    pub regular_availability: super::Intervals<NaiveDateTime>,
     // last booking end time (if any)
     pub last_booking_end: Option<NaiveDateTime>,
+    // onboarding profile (stage end, speed), sorted by stage end
+    pub ramp_up: Vec<(NaiveDateTime, f64)>,
+}
+
+impl Resource {
+    /// Speed relative to an experienced resource at the given time
+    pub fn ramp_up_speed(&self, time: NaiveDateTime) -> f64 {
+        self.ramp_up.iter().find(|(end, _)| time < *end).map_or(1.0, |(_, speed)| *speed)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -107,8 +131,11 @@ pub struct ResourceConstraint {
 
 #[derive(Debug, Clone)]
 pub struct ResourceConstraintEntry {
-    pub db_id: i32,
+    // None for the members of a team constraint
+    pub db_id: Option<i32>,
     pub resource: Weak<RefCell<Resource>>,
+    // membership periods of team members, the resource can only work on the task within them
+    pub window: Option<super::Intervals<NaiveDateTime>>,
 }
 
 // ### resulting plan
@@ -119,6 +146,18 @@ pub struct Plan {
     pub fulfilled_milestones: HashMap<i32, FulfilledMilestone>,
     // collected issues during planning: (code, description, optional task_id)
     pub issues: Vec<PlanningIssue>,
+    // milestones delayed by other projects (filled after the GA, see `find_contentions`)
+    pub contentions: Vec<Contention>,
+}
+
+/// A milestone that would be reached earlier without the tasks of another project
+#[derive(Debug, Clone)]
+pub struct Contention {
+    pub milestone_id: i32,
+    // the competing project
+    pub project_id: i32,
+    pub planned_date: Option<NaiveDateTime>,
+    pub unaffected_date: NaiveDateTime,
 }
 
 #[derive(Debug, Clone)]
@@ -5,12 +5,15 @@ use std::fmt::Display;
 use std::rc::{Rc, Weak};
 use std::str::FromStr;
 
+use crate::gql::calculation_run::{CalculationResult, CalculationTrigger};
 use crate::gql::context::Context;
 use crate::gql::issue::IssueType;
+use crate::gql::ramp_up_stage::MIN_RAMP_UP_SPEED;
 // availability now loaded via Context::load_combined_availability
-use crate::scheduling::{Bound, Interval, Intervals, datastructures::*};
-use crate::{entity::*, gql::task::TaskDesignation};
-use chrono::NaiveDateTime;
+use crate::gql::dataloader::{query_focus_factors, query_regular_availability};
+use crate::scheduling::{Bound, Interval, Intervals, datastructures::*, ga::GASettings};
+use crate::{entity::*, gql::project::ProjectPriority, gql::task::TaskDesignation};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use itertools::Itertools;
 use petgraph::Direction::{Incoming, Outgoing};
 use petgraph::Graph;
@@ -20,7 +23,8 @@ use petgraph::dot::{Config, Dot};
 use petgraph::graph::NodeIndex;
 use petgraph::prelude::StableGraph;
 use petgraph::visit::{EdgeRef as _, IntoNodeReferences};
-use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter};
+use sea_orm::sea_query::Expr;
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
 use tokio::task::JoinSet;
 
 pub async fn query_problem(ctx: &Context) -> anyhow::Result<Project> {
@@ -29,19 +33,51 @@ pub async fn query_problem(ctx: &Context) -> anyhow::Result<Project> {
     // alternatively: on marking milestones as done, check which tasks (and requirements) can be
     // marked as not relevant anymore?
     let db = ctx.txn().await?;
-    let db_task_vec = task::Entity::find().all(db).await?;
-    let db_resource_vec = resource::Entity::find().all(db).await?;
-    let db_dependencies_vec = dependency::Entity::find().all(db).await?;
+    // Tasks of all projects are planned together, as resources are shared across projects.
+    // Everything is processed ordered by id: the graph and the constraints must be built the same
+    // way each time, so runs with the same seed are reproducible.
+    let db_task_vec = task::Entity::find().order_by_asc(task::Column::Id).all(db).await?;
+    let db_resource_vec =
+        resource::Entity::find().order_by_asc(resource::Column::Id).all(db).await?;
+    let db_ramp_up_vec = ramp_up_stage::Entity::find().all(db).await?;
+    let db_dependencies_vec =
+        dependency::Entity::find().order_by_asc(dependency::Column::Id).all(db).await?;
     let task_ids = db_task_vec.iter().map(|t| t.id).collect::<Vec<_>>();
     let db_constraints_vec = resource_constraint::Entity::find()
         .filter(resource_constraint::Column::TaskId.is_in(task_ids))
+        .order_by_asc(resource_constraint::Column::Id)
         .all(db)
         .await?;
     let constraint_ids = db_constraints_vec.iter().map(|t| t.id).collect::<Vec<_>>();
     let db_constraint_entries_vec = resource_constraint_entry::Entity::find()
         .filter(resource_constraint_entry::Column::ResourceConstraintId.is_in(constraint_ids))
+        .order_by_asc(resource_constraint_entry::Column::Id)
         .all(db)
         .await?;
+    // members of the teams referenced by constraints, including future ones. Members who already
+    // left cannot work on anything planned from now on.
+    let now = chrono::Utc::now();
+    let team_ids = db_constraints_vec.iter().filter_map(|c| c.team_id).unique().collect::<Vec<_>>();
+    let db_memberships_vec = team_membership::Entity::find()
+        .filter(team_membership::Column::TeamId.is_in(team_ids))
+        .order_by_asc(team_membership::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|m| m.left.is_none_or(|left| left > now))
+        .collect::<Vec<_>>();
+
+    // slope index of each project's priority, tasks without project use medium priority
+    let project_priorities = project::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| {
+            let priority =
+                ProjectPriority::from_str(&p.priority).unwrap_or(ProjectPriority::Medium);
+            (p.id, priority.slope_index())
+        })
+        .collect::<HashMap<i32, usize>>();
 
     let db_task_map = db_task_vec.into_iter().map(|t| (t.id, t)).collect::<HashMap<i32, _>>();
 
@@ -90,6 +126,38 @@ pub async fn query_problem(ctx: &Context) -> anyhow::Result<Project> {
     for ce in db_constraint_entries_vec.iter() {
         constraint_entries_map.entry(ce.resource_constraint_id).or_default().push(ce.resource_id);
     }
+    // team constraints follow the team's membership, resources used by other constraints of the
+    // same task are skipped (each resource can only be used once per task)
+    let mut team_entries: Vec<(i32, i32, Intervals<NaiveDateTime>)> = vec![];
+    for c in db_constraints_vec.iter() {
+        let Some(team_id) = c.team_id else {
+            continue;
+        };
+        let used = db_constraints_vec
+            .iter()
+            .filter(|other| other.task_id == c.task_id && other.id != c.id)
+            .flat_map(|other| constraint_entries_map.get(&other.id).cloned().unwrap_or_default())
+            .collect::<Vec<_>>();
+        for m in db_memberships_vec.iter().filter(|m| m.team_id == team_id) {
+            if used.contains(&m.resource_id) {
+                continue;
+            }
+            // the resource works on the task while it is a member, several memberships of the
+            // same resource are merged into one entry
+            let end = m.left.map_or(NaiveDateTime::MAX, |left| left.naive_utc());
+            let period = Interval::new_lcro(m.joined.naive_utc(), end);
+            match team_entries
+                .iter_mut()
+                .find(|(cid, rid, _)| *cid == c.id && *rid == m.resource_id)
+            {
+                Some((_, _, window)) => window.insert(period),
+                None => {
+                    constraint_entries_map.entry(c.id).or_default().push(m.resource_id);
+                    team_entries.push((c.id, m.resource_id, period.into()));
+                }
+            }
+        }
+    }
 
     // Build task -> constraints info map: task_id -> Vec<(constraint_id, speed, entries_vec, optional)>
     let mut task_constraints_map: HashMap<i32, Vec<(i32, f64, Vec<i32>, bool)>> = HashMap::new();
@@ -125,8 +193,13 @@ pub async fn query_problem(ctx: &Context) -> anyhow::Result<Project> {
         } else if t.designation.as_str() == <&'static str>::from(TaskDesignation::Milestone) {
             let new_ref = Rc::new(RefCell::new(Milestone {
                 db_id: t.id,
+                project_id: t.project_id,
                 title: t.title.clone(),
                 schedule_target: t.schedule_target.map(|dt| dt.naive_utc()).unwrap_or_default(),
+                priority: t
+                    .project_id
+                    .and_then(|p| project_priorities.get(&p).cloned())
+                    .unwrap_or(ProjectPriority::Medium.slope_index()),
             }));
             project_objects.milestones.push(Rc::clone(&new_ref));
             Node::Milestone(new_ref.into())
@@ -181,9 +254,20 @@ pub async fn query_problem(ctx: &Context) -> anyhow::Result<Project> {
             let remaining_effort = (base_effort - booked_amount_days).max(1.0).min(base_effort);
             let new_ref = Rc::new(RefCell::new(Task {
                 db_id: t.id,
+                project_id: t.project_id,
                 parent: None,
                 title: t.title.clone(),
                 effort: remaining_effort,
+                split_policy: match crate::gql::task::SplitPolicy::from_str(&t.split_policy) {
+                    Ok(crate::gql::task::SplitPolicy::Contiguous) => SplitPolicy::Contiguous,
+                    Ok(crate::gql::task::SplitPolicy::Limited) => SplitPolicy::MaxInterruptions(
+                        t.max_interruptions.unwrap_or(0).max(0) as usize,
+                    ),
+                    Ok(crate::gql::task::SplitPolicy::Free) => SplitPolicy::Free,
+                    Err(_) => {
+                        anyhow::bail!("Unknown split policy {:?} of task {}", t.split_policy, t.id)
+                    }
+                },
                 constraints: Vec::new(), // filled later
                 booked_until,
                 booked_resources: booked_resources_vec,
@@ -230,7 +314,7 @@ pub async fn query_problem(ctx: &Context) -> anyhow::Result<Project> {
         .collect::<HashMap<i32, _>>();
 
     // add parent links
-    for t in db_task_map.values() {
+    for t in db_task_map.values().sorted_by_key(|t| t.id) {
         let designation =
             TaskDesignation::from_str(&t.designation).expect("Must have a valid designation");
         if let Some(pid) = t.parent_id {
@@ -283,12 +367,23 @@ pub async fn query_problem(ctx: &Context) -> anyhow::Result<Project> {
         .into_iter()
         .map(|rm| {
             let last = resource_last_booking.get(&rm.id).cloned();
+            let ramp_up = db_ramp_up_vec
+                .iter()
+                .filter(|s| s.resource_id == rm.id)
+                .map(|s| {
+                    let speed = s.speed.max(MIN_RAMP_UP_SPEED) as f64;
+                    (rm.added.naive_utc() + chrono::TimeDelta::days(s.days as i64), speed)
+                })
+                .sorted_by_key(|(end, _)| *end)
+                .collect();
             Rc::new(RefCell::new(Resource {
                 db_id: rm.id,
                 name: rm.name,
                 timezone: rm.timezone,
                 slots: vec![],
+                regular_availability: Intervals::new(),
                 last_booking_end: last,
+                ramp_up,
             }))
         })
         .collect();
@@ -301,6 +396,8 @@ pub async fn query_problem(ctx: &Context) -> anyhow::Result<Project> {
         .map(|r| (r.borrow().db_id, Rc::clone(r)))
         .collect::<HashMap<i32, _>>();
 
+    let team_constraint_ids =
+        db_constraints_vec.iter().filter(|c| c.team_id.is_some()).map(|c| c.id).collect_vec();
     let mut constraint_map = db_constraints_vec
         .into_iter()
         .map(|c| {
@@ -322,10 +419,28 @@ pub async fn query_problem(ctx: &Context) -> anyhow::Result<Project> {
         let (c, _) =
             constraint_map.get_mut(&ce.resource_constraint_id).expect("constraint must exist.");
         let r = resource_map.get(&ce.resource_id).expect("resource must exist.");
-        let entry = ResourceConstraintEntry { db_id: ce.id, resource: Rc::downgrade(r) };
+        let entry = ResourceConstraintEntry {
+            db_id: Some(ce.id),
+            resource: Rc::downgrade(r),
+            window: None,
+        };
         c.constraints.push(entry);
     }
-    for (c, task_id) in constraint_map.into_values() {
+    for (cid, resource_id, window) in team_entries {
+        let (c, _) = constraint_map.get_mut(&cid).expect("constraint must exist.");
+        let r = resource_map.get(&resource_id).expect("resource must exist.");
+        c.constraints.push(ResourceConstraintEntry {
+            db_id: None,
+            resource: Rc::downgrade(r),
+            window: Some(window),
+        });
+    }
+    for (_, (c, task_id)) in constraint_map.into_iter().sorted_by_key(|(cid, _)| *cid) {
+        // a team without members cannot fulfill the constraint: optional ones are
+        // dropped, required ones are kept and reported when planning the task
+        if c.optional && c.constraints.is_empty() && team_constraint_ids.contains(&c.db_id) {
+            continue;
+        }
         if let Some(group) = group_map.get(&task_id) {
             group.borrow_mut().constraints.push(c);
         } else if let Some(task) = task_map.get(&task_id) {
@@ -543,17 +658,27 @@ pub async fn query_slots(
         set.spawn(async move { (idx, fut.await) });
     }
 
+    let resource_ids = resources.iter().map(|r| r.borrow().db_id).collect::<Vec<_>>();
+    let focus = query_focus_factors(ctx, &resource_ids).await?;
+
     while let Some(join_res) = set.join_next().await {
         match join_res {
             Ok((idx, Ok(iv))) => {
-                // apply availability immediately for the resource at `idx`
+                // apply availability immediately for the resource at `idx`, scaled down to the
+                // share the resource spends on planned tasks
                 let r = &resources[idx];
-                add_slot_availability(r, iv, start, end)?;
+                add_slot_availability(r, focus[idx].apply(&iv), start, end)?;
             }
             Ok((_, Err(e))) => return Err(e),
             Err(e) => return Err(anyhow::anyhow!("Join error: {}", e)),
         }
     }
+
+    // the time not spent on planned tasks does not interrupt them
+    let regular = query_regular_availability(ctx, &resource_ids, start, end).await?;
+    for ((r, iv), focus) in resources.iter().zip(regular).zip(focus.iter()) {
+        r.borrow_mut().regular_availability = focus.apply(&iv);
+    }
     Ok(())
 }
 
@@ -592,8 +717,36 @@ pub fn reduce_graph(g: &mut Graph<Node, ()>) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub async fn store_plan(ctx: &Context, project: &Project, plan: &Plan) -> anyhow::Result<()> {
+/// Store the plan and issues of a calculation that started at `calculated_at`
+pub async fn store_plan(
+    ctx: &Context,
+    project: &Project,
+    plan: &Plan,
+    calculated_at: DateTime<Utc>,
+) -> anyhow::Result<()> {
     let txn = ctx.txn().await?;
+    // all projects are planned together, projects modified since the calculation started are
+    // still outdated
+    project::Entity::update_many()
+        .col_expr(project::Column::CalculatedAt, Expr::value(Some(calculated_at)))
+        .filter(
+            project::Column::ModifiedAt
+                .is_null()
+                .or(project::Column::ModifiedAt.lte(calculated_at)),
+        )
+        .exec(txn)
+        .await?;
+    milestone_contention::Entity::delete_many().exec(txn).await?;
+    for c in &plan.contentions {
+        let am = milestone_contention::ActiveModel {
+            id: ActiveValue::NotSet,
+            milestone_id: ActiveValue::Set(c.milestone_id),
+            project_id: ActiveValue::Set(c.project_id),
+            planned_date: ActiveValue::Set(c.planned_date.map(|d| d.and_utc())),
+            unaffected_date: ActiveValue::Set(c.unaffected_date.and_utc()),
+        };
+        am.insert(txn).await?;
+    }
     // only remove previous planning allocations and their allocated_resource entries
     use crate::gql::allocation::AllocationType;
     let plan_allocs: Vec<i32> = allocation::Entity::find()
@@ -697,3 +850,55 @@ pub async fn store_plan(ctx: &Context, project: &Project, plan: &Plan) -> anyhow
     }
     Ok(())
 }
+
+/// Outcome of a run of the scheduler
+pub struct CalculationRun {
+    pub trigger: CalculationTrigger,
+    pub result: CalculationResult,
+    pub started_at: DateTime<Utc>,
+    pub seed: u64,
+    /// (milestone id, title, cost), empty if the run did not finish
+    pub milestone_costs: Vec<(i32, String, f64)>,
+    pub issue_count: Option<usize>,
+}
+
+/// Store the record of a calculation run, it is finished now
+pub async fn store_run(
+    ctx: &Context,
+    run: &CalculationRun,
+    settings: &GASettings,
+) -> anyhow::Result<()> {
+    let txn = ctx.txn().await?;
+    let finished = run.result == CalculationResult::Finished;
+    let am = calculation_run::ActiveModel {
+        id: ActiveValue::NotSet,
+        trigger: ActiveValue::Set(run.trigger.into()),
+        result: ActiveValue::Set(run.result.into()),
+        started_at: ActiveValue::Set(run.started_at),
+        finished_at: ActiveValue::Set(Utc::now()),
+        settings: ActiveValue::Set(serde_json::to_string(settings)?),
+        // stored as bit pattern, sqlite has no unsigned 64 bit integers
+        seed: ActiveValue::Set(run.seed as i64),
+        cost: ActiveValue::Set(
+            finished.then(|| run.milestone_costs.iter().map(|(_, _, cost)| cost).sum()),
+        ),
+        issue_count: ActiveValue::Set(
+            run.issue_count.filter(|_| finished).map(|count| count as i32),
+        ),
+    };
+    let db_run = am.insert(txn).await?;
+    if !finished {
+        return Ok(());
+    }
+    for (milestone_id, title, cost) in &run.milestone_costs {
+        let am = calculation_run_cost::ActiveModel {
+            id: ActiveValue::NotSet,
+            calculation_run_id: ActiveValue::Set(db_run.id),
+            milestone_id: ActiveValue::Set(Some(*milestone_id)),
+            milestone_title: ActiveValue::Set(title.clone()),
+            cost: ActiveValue::Set(*cost),
+        };
+        am.insert(txn).await?;
+    }
+    Ok(())
+}
@@ -7,22 +7,26 @@ use petgraph::{
     Graph,
     graph::NodeIndex,
 };
-use rand::{Rng as _, seq::IndexedRandom as _};
+use rand::{Rng, SeedableRng as _, rngs::StdRng, seq::IndexedRandom as _};
+use serde::Serialize;
 use std::{
     cell::RefCell,
     collections::{HashMap, HashSet},
+    ops::ControlFlow,
     rc::{Rc, Weak},
-    time::Instant,
+    time::{Duration, Instant},
 };
-use tracing::warn;
+use tracing::{info, warn};
 
 use crate::scheduling::{
-    Interval, Intervals, Milestone, Plan, PlanningIssue, ResourceConstraint, Slot,
+    Contention, Interval, Intervals, Milestone, Plan, PlanningIssue, ResourceConstraint,
+    ResourceConstraintEntry, Slot,
 };
 
-use super::datastructures::{Node, Project, Task};
+use super::datastructures::{Node, Project, SplitPolicy, Task};
 
 /// Settings for the genetic algorithm.
+#[derive(Serialize)]
 pub struct GASettings {
     pub iterations: usize,
     pub population: usize,
@@ -69,6 +73,8 @@ pub struct TaskGene {
     pub task_nidx: NodeIndex,
     pub required_resource_ids: HashSet<i32>,
     pub selectable_resource_ids: Vec<i32>,
+    // membership periods of the chosen team members, see `ResourceConstraintEntry::window`
+    pub resource_windows: HashMap<i32, Intervals<NaiveDateTime>>,
     // booking metadata: whether this task has bookings and the first booking start
     pub is_booked: bool,
     pub booking_start: Option<NaiveDateTime>,
@@ -86,7 +92,7 @@ pub struct Individual {
     pub finished_tasks: Vec<TaskGene>,
 }
 
-pub fn generate_random_individual(project: &Project) -> Individual {
+pub fn generate_random_individual(project: &Project, rng: &mut impl Rng) -> Individual {
     // TODO: not all allowed random orders are created with the same probability.
     // Example:
     // Assume we have 3 tasks (T1, T2, T3) and T2 depends on T1.
@@ -97,7 +103,6 @@ pub fn generate_random_individual(project: &Project) -> Individual {
     // For larger examples (with longer task chains), we might miss out on relevant parts of the
     // solution space. These possibilities are also never recovered using crossover and are also
     // unlikely to happen during simple swap mutations.
-    let mut rng = rand::rng();
     let mut task_genes = vec![];
     let mut possible = project.g.externals(Direction::Incoming).collect::<Vec<_>>();
     let mut handled = HashSet::new();
@@ -106,7 +111,7 @@ pub fn generate_random_individual(project: &Project) -> Individual {
         let nidx = possible.swap_remove(chosen_idx);
         handled.insert(nidx);
         if let Node::Task(task) = project.g.node_weight(nidx).expect("node must exist") {
-            task_genes.push(create_random_task_gene(project, Rc::clone(&task), nidx))
+            task_genes.push(create_random_task_gene(project, Rc::clone(&task), nidx, rng))
         }
         for candidate in project.g.neighbors_directed(nidx, Direction::Outgoing) {
             let requirements = project
@@ -143,8 +148,7 @@ pub fn milestone_cost(
     plan: &Plan,
     milestone: &Milestone,
 ) -> f64 {
-    // since no priority metadata exists, use medium priority index = 1
-    let pri_idx = 1usize;
+    let pri_idx = milestone.priority;
     let day = 3600.0 * 24.0;
     if let Some(fulfilled_milestone) = plan.fulfilled_milestones.get(&milestone.db_id) {
         let diff = fulfilled_milestone.date - milestone.schedule_target;
@@ -179,36 +183,82 @@ pub fn cost_function(project: &Project, settings: &GASettings, ind: &Individual)
 // c* ln(t +delta) -> c/(t+delta) ->
 // c*sqrt(t+delta) -> c * 0.5 *(t+delta)**3/2
 
+/// Progress of a running genetic algorithm
+#[derive(Clone, Debug)]
+pub struct GAProgress {
+    /// Number of finished iterations
+    pub iteration: usize,
+    pub iterations: usize,
+    /// Cost of the best individual so far
+    pub best_cost: f64,
+    pub elapsed: Duration,
+}
+
+impl GAProgress {
+    /// Estimated time until all iterations are finished, extrapolated from the finished ones
+    pub fn remaining(&self) -> Option<Duration> {
+        if self.iteration == 0 {
+            return None;
+        }
+        let left = self.iterations.saturating_sub(self.iteration);
+        Some(self.elapsed.mul_f64(left as f64 / self.iteration as f64))
+    }
+}
+
+fn best_cost(population: &[(Individual, f64)]) -> f64 {
+    population.iter().map(|(_, c)| *c).fold(f64::INFINITY, f64::min)
+}
+
 /// Run the genetic algorithm and return the best found individual.
 ///
-/// NOTE: The project model currently does not contain per-milestone priority metadata.
-/// As a pragmatic default we treat all milestones as 'medium' priority. If you later
-/// add priority information to milestones, the cost function here should be adapted to
-/// read it and pick the appropriate slope index.
-pub fn run_ga(project: &Project, settings: &GASettings) -> Individual {
+/// Milestones are weighted by the priority of their project (low, medium or high slopes of the
+/// cost function), so higher priority projects win when they compete for the same resources.
+///
+/// Runs with the same `seed` and project explore the same individuals. `on_progress` is called
+/// after the initial population and after every iteration, the run is cancelled (and none
+/// returned) when it breaks.
+pub fn run_ga(
+    project: &Project,
+    settings: &GASettings,
+    seed: u64,
+    mut on_progress: impl FnMut(&GAProgress) -> ControlFlow<()>,
+) -> Option<Individual> {
     let start_time = Instant::now();
-    let mut rng = rand::rng();
+    let mut report = |iteration: usize, population: &[(Individual, f64)]| {
+        on_progress(&GAProgress {
+            iteration,
+            iterations: settings.iterations,
+            best_cost: best_cost(population),
+            elapsed: start_time.elapsed(),
+        })
+    };
+    let mut rng = StdRng::seed_from_u64(seed);
 
     let cost_of = |ind: &Individual| -> f64 { cost_function(project, settings, ind) };
     // initial population
     let mut population: Vec<(Individual, f64)> = (0..settings.population)
         .map(|_| {
-            let ind = generate_random_individual(project);
+            let ind = generate_random_individual(project, &mut rng);
             let c = cost_of(&ind);
             (ind, c)
         })
         .collect();
 
+    if report(0, &population).is_break() {
+        info!("calculation cancelled before the first iteration");
+        return None;
+    }
+
     // ensure keep_seeds is not larger than population
     let keep_seeds = settings.keep_seeds.min(settings.population);
 
     // iterate
-    for _it in 0..settings.iterations {
+    for it in 0..settings.iterations {
         // sort by cost ascending
         population.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
         println!(
             "iteration: {} | Best: {} | Worst: {}",
-            _it,
+            it,
             population.first().expect("Cannot be empty").1,
             population.last().expect("Cannot be empty").1
         );
@@ -236,9 +286,9 @@ pub fn run_ga(project: &Project, settings: &GASettings) -> Individual {
                     + settings.prob_both);
 
             // choose parents
-            let parent_from_seed = |rng: &mut _| -> Individual {
+            let parent_from_seed = |rng: &mut StdRng| -> Individual {
                 if seeds.is_empty() {
-                    generate_random_individual(project)
+                    generate_random_individual(project, rng)
                 } else {
                     seeds.choose(rng).unwrap().clone()
                 }
@@ -324,7 +374,7 @@ pub fn run_ga(project: &Project, settings: &GASettings) -> Individual {
                 parent_from_seed(&mut rng)
             } else {
                 // default: random individual
-                generate_random_individual(project)
+                generate_random_individual(project, &mut rng)
             };
 
             // apply mutation if requested or mode==both or mode==mutation
@@ -347,8 +397,12 @@ pub fn run_ga(project: &Project, settings: &GASettings) -> Individual {
                     if tg.is_booked {
                         continue;
                     }
-                    let new_tg =
-                        create_random_task_gene(project, Rc::clone(&tg.task), tg.task_nidx);
+                    let new_tg = create_random_task_gene(
+                        project,
+                        Rc::clone(&tg.task),
+                        tg.task_nidx,
+                        &mut rng,
+                    );
                     // replace resource-related fields (keep Rc pointers)
                     child.tasks[t_idx].required_resource_ids = new_tg.required_resource_ids;
                     child.tasks[t_idx].selectable_resource_ids = new_tg.selectable_resource_ids;
@@ -369,6 +423,11 @@ pub fn run_ga(project: &Project, settings: &GASettings) -> Individual {
 
         // ensure best individual so far is preserved
         population.push((best.clone(), cost_of(&best)));
+
+        if report(it + 1, &population).is_break() {
+            info!("calculation cancelled after iteration {}", it);
+            return None;
+        }
     }
 
     // final sort and return best individual
@@ -380,17 +439,17 @@ pub fn run_ga(project: &Project, settings: &GASettings) -> Individual {
         population.last().expect("Cannot be empty").1
     );
     println!("Took {} seconds", (end_time - start_time).as_secs_f64());
-    population.first().expect("population must not be empty").0.clone()
+    Some(population.first().expect("population must not be empty").0.clone())
 }
 
 pub fn create_random_task_gene(
     _project: &Project,
     task: Rc<RefCell<Task>>,
     nidx: NodeIndex,
+    rng: &mut impl Rng,
 ) -> TaskGene {
     let borrowed_task = task.borrow();
     // constraints are available via borrowed_task.constraints
-    let mut rng = rand::rng();
     let mut required_resource_ids: HashSet<i32> = HashSet::new();
     let mut used_constraint_speeds: Vec<f64> = Vec::new();
 
@@ -399,6 +458,14 @@ pub fn create_random_task_gene(
     // prefer to use those resources when they match constraints. We only alter
     // selection logic at the end based on whether bookings were present.
     let mut booked_res_ids: HashSet<i32> = HashSet::new();
+    let mut resource_windows: HashMap<i32, Intervals<NaiveDateTime>> = HashMap::new();
+    let mut use_entry = |entry: &ResourceConstraintEntry| {
+        let rid = Weak::upgrade(&entry.resource).expect("resource must still exist").borrow().db_id;
+        if let Some(window) = &entry.window {
+            resource_windows.insert(rid, window.clone());
+        }
+        rid
+    };
     for (_s, _e, ress, _f) in borrowed_task.bookings.iter() {
         for r in ress.iter() {
             booked_res_ids.insert(*r);
@@ -412,6 +479,10 @@ pub fn create_random_task_gene(
     // If bookings exist, prefer booked resources that match constraints.
     // Otherwise put the constraint into the required / optional vec.
     for c in borrowed_task.constraints.iter() {
+        // constraints without resources (teams without members) are reported by `plan_task`
+        if c.constraints.is_empty() {
+            continue;
+        }
         // try to find a booked resource matching this constraint
         let mut chosen: Option<i32> = None;
         if !booked_res_ids.is_empty() {
@@ -421,7 +492,7 @@ pub fn create_random_task_gene(
                     .borrow()
                     .db_id;
                 if booked_res_ids.contains(&rid) {
-                    chosen = Some(rid);
+                    chosen = Some(use_entry(entry));
                     break;
                 }
             }
@@ -443,7 +514,7 @@ pub fn create_random_task_gene(
     // required constraints
     if booked_res_ids.is_empty() && !opt_constraints.is_empty() {
         let num_opt: usize = rng.random_range(..=opt_constraints.len());
-        req_constraints.extend(opt_constraints.choose_multiple(&mut rng, num_opt));
+        req_constraints.extend(opt_constraints.choose_multiple(rng, num_opt));
     }
 
     // Determine selectable_resource_ids: pick the largest required constraint
@@ -458,17 +529,14 @@ pub fn create_random_task_gene(
         let chosen = req_constraints.remove(max_idx);
         used_constraint_speeds.push(chosen.speed);
         for entry in chosen.constraints.iter() {
-            let rid =
-                Weak::upgrade(&entry.resource).expect("resource must still exist").borrow().db_id;
-            selectable_resource_ids.push(rid);
+            selectable_resource_ids.push(use_entry(entry));
         }
     }
 
     // choose a resource randomly for the remaining required constraints
     for c in req_constraints {
-        let entry = c.constraints.choose(&mut rng).expect("constraint must have an entry");
-        let rid = Weak::upgrade(&entry.resource).expect("resource must still exist").borrow().db_id;
-        required_resource_ids.insert(rid);
+        let entry = c.constraints.choose(rng).expect("constraint must have an entry");
+        required_resource_ids.insert(use_entry(entry));
         used_constraint_speeds.push(c.speed);
     }
 
@@ -490,12 +558,80 @@ pub fn create_random_task_gene(
         task_nidx: nidx,
         required_resource_ids,
         selectable_resource_ids,
+        resource_windows,
         is_booked,
         booking_start,
         total_speed,
     }
 }
 
+/// Find milestones delayed by tasks of another project. For each project, the individual is
+/// planned again without that project's tasks (except those other projects depend on) and the
+/// milestone dates of the remaining projects are compared with `plan`.
+pub fn find_contentions(
+    project: &Project,
+    individual: &Individual,
+    plan: &Plan,
+) -> Vec<Contention> {
+    let node_project = |nidx: NodeIndex| match project.g.node_weight(nidx) {
+        Some(Node::Task(t)) => t.borrow().project_id,
+        Some(Node::Milestone(m)) => m.borrow().project_id,
+        _ => None,
+    };
+    let project_ids =
+        project.objs.tasks.iter().filter_map(|t| t.borrow().project_id).unique().sorted();
+    let mut result = vec![];
+    for competing in project_ids {
+        // keep everything outside the competing project, including its predecessors
+        let mut needed = HashSet::new();
+        let mut stack = project
+            .g
+            .node_indices()
+            .filter(|nidx| node_project(*nidx) != Some(competing))
+            .collect::<Vec<_>>();
+        while let Some(nidx) = stack.pop() {
+            if needed.insert(nidx) {
+                stack.extend(project.g.neighbors_directed(nidx, Incoming));
+            }
+        }
+        let keep = |genes: &[TaskGene]| {
+            genes.iter().filter(|tg| needed.contains(&tg.task_nidx)).cloned().collect::<Vec<_>>()
+        };
+        let reduced = Individual {
+            booked_tasks: keep(&individual.booked_tasks),
+            tasks: keep(&individual.tasks),
+            finished_tasks: individual.finished_tasks.clone(),
+        };
+        if reduced.booked_tasks.len() == individual.booked_tasks.len()
+            && reduced.tasks.len() == individual.tasks.len()
+        {
+            continue;
+        }
+        let unaffected = plan_individual(project, &reduced);
+        for m in project.objs.milestones.iter() {
+            let m = m.borrow();
+            if m.project_id.is_none_or(|p| p == competing) {
+                continue;
+            }
+            let Some(unaffected_date) =
+                unaffected.fulfilled_milestones.get(&m.db_id).map(|f| f.date)
+            else {
+                continue;
+            };
+            let planned_date = plan.fulfilled_milestones.get(&m.db_id).map(|f| f.date);
+            if planned_date.is_none_or(|date| date > unaffected_date) {
+                result.push(Contention {
+                    milestone_id: m.db_id,
+                    project_id: competing,
+                    planned_date,
+                    unaffected_date,
+                });
+            }
+        }
+    }
+    result
+}
+
 pub fn plan_individual(project: &Project, individual: &Individual) -> Plan {
     let mut plan = Plan::default();
     // prepare resource slots (do not truncate by booking here; query_slots already requested per-resource ranges)
@@ -654,12 +790,19 @@ pub fn plan_individual(project: &Project, individual: &Individual) -> Plan {
 struct _SlotIterator<'a> {
     resource_id: i32,
     slots: &'a Vec<Slot>,
+    // membership periods of a team member, the slots are only usable within them
+    window: Option<&'a Intervals<NaiveDateTime>>,
     current_idx: usize,
 }
 
 impl<'a> _SlotIterator<'a> {
-    fn new(resource_id: i32, slots: &'a Vec<Slot>, start: NaiveDateTime) -> Self {
-        let mut result = Self { resource_id, slots, current_idx: 0 };
+    fn new(
+        resource_id: i32,
+        slots: &'a Vec<Slot>,
+        window: Option<&'a Intervals<NaiveDateTime>>,
+        start: NaiveDateTime,
+    ) -> Self {
+        let mut result = Self { resource_id, slots, window, current_idx: 0 };
         result.ensure_start(start);
         result
     }
@@ -676,6 +819,15 @@ impl<'a> _SlotIterator<'a> {
         self.slots.get(self.current_idx)
     }
 
+    /// Usable intervals of the current slot
+    fn current_intervals(&self) -> Option<Intervals<NaiveDateTime>> {
+        let slot = self.current()?;
+        Some(match self.window {
+            Some(window) => slot.intervals.intersection(window),
+            None => slot.intervals.clone(),
+        })
+    }
+
     fn advance(&mut self) {
         self.current_idx += 1;
     }
@@ -730,8 +882,8 @@ fn _slot_intervals(
     let mut result = Intervals::new();
     result.insert(Interval::new_lcro(task_start, project.calculation_end));
     for si in slot_iterators.iter() {
-        if let Some(slot) = si.current() {
-            result = result.intersection(&slot.intervals);
+        if let Some(intervals) = si.current_intervals() {
+            result = result.intersection(&intervals);
         } else {
             return Err(anyhow::anyhow!("Failed to combine slot intervals"));
         }
@@ -746,7 +898,8 @@ pub fn plan_task(
     g_finished: &mut Graph<Option<NaiveDateTime>, ()>,
 ) -> Result<HashMap<i32, Slot>, Option<PlanningIssue>> {
     let task = task_gene.task.borrow();
-    let res_ids: Vec<_> = task_gene.required_resource_ids.iter().cloned().collect();
+    // sorted, the order of a HashSet differs between processes
+    let res_ids: Vec<_> = task_gene.required_resource_ids.iter().cloned().sorted().collect();
     let task_start_opt = match g_finished
         .neighbors_directed(task_gene.task_nidx, Incoming)
         .map(|nidx| g_finished.node_weight(nidx).cloned().flatten())
@@ -780,8 +933,17 @@ pub fn plan_task(
             task_id: Some(task.db_id),
         })); // detected on creation
     }
+    if task.constraints.iter().any(|c| !c.optional && c.constraints.is_empty()) {
+        return Err(Some(PlanningIssue {
+            code: crate::gql::issue::IssueCode::ResourceMissing,
+            description: "A required team has no members.".to_string(),
+            task_id: Some(task.db_id),
+        }));
+    }
     // divide effort by total_speed to account for faster/slower constraints
     let effective_hours = task.effort / task_gene.total_speed;
+    // working time needed by experienced resources, onboarding resources need longer (see
+    // `_select_intervals`)
     let effort = TimeDelta::seconds((effective_hours * 8.0 * 3600.0).round() as i64);
 
     // Determine selectable resources: prefer gene.selectable. If empty, we will
@@ -795,6 +957,7 @@ pub fn plan_task(
             _SlotIterator::new(
                 res_id,
                 resource_slots.get(&res_id).expect("Resource slots must exist"),
+                task_gene.resource_windows.get(&res_id),
                 task_start,
             )
         })
@@ -814,6 +977,7 @@ pub fn plan_task(
             _SlotIterator::new(
                 rid,
                 resource_slots.get(&rid).expect("Resource slots must exist"),
+                task_gene.resource_windows.get(&rid),
                 task_start,
             )
         })
@@ -858,9 +1022,16 @@ pub fn plan_task(
             None;
         for sel_iter in selectable_iterators.iter_mut() {
             loop {
-                if let Some(sel_slot) = sel_iter.current() {
-                    let inter = primary_intervals.intersection(&sel_slot.intervals);
-                    if inter.length().unwrap_or_default() >= effort {
+                if let Some(sel_intervals) = sel_iter.current_intervals() {
+                    let inter = primary_intervals.intersection(&sel_intervals);
+                    let mut involved_ids = res_ids.clone();
+                    involved_ids.push(sel_iter.resource_id);
+                    let ramp_up = _ramp_up(project, &involved_ids);
+                    let selected =
+                        _select_intervals(&inter, effort, task.split_policy, &ramp_up, |gap| {
+                            _is_interruption(project, &involved_ids, gap)
+                        });
+                    if let Some(assigned_intervals) = selected {
                         // feasible candidate: build result map and removals
                         let mut result_map: HashMap<i32, Slot> = HashMap::new();
                         let mut removals: HashMap<i32, usize> = HashMap::new();
@@ -875,16 +1046,12 @@ pub fn plan_task(
                         result_map.insert(sel_iter.resource_id, sslot.clone());
                         removals.insert(sel_iter.resource_id, sidx);
 
-                        let assigned_intervals = _reduce_intervals(
-                            primary_intervals.intersection(&sslot.intervals),
-                            effort,
-                        );
                         let hull = assigned_intervals.hull().expect("Cannot be empty");
                         let end_ts = hull.end().value().expect("no unbounded intervals");
                         let assigned_slot = Slot {
                             range: hull,
                             extensible: false,
-                            duration: effort,
+                            duration: assigned_intervals.length().expect("no unbound intervals"),
                             intervals: assigned_intervals,
                         };
                         for rid in result_map.keys().cloned().collect::<Vec<_>>() {
@@ -927,15 +1094,20 @@ pub fn plan_task(
 
         // If there are no selectable iterators (and thus no best_candidate so far) try primary-only candidate
         if best_candidate.is_none() && selectable_iterators.is_empty() {
-            // We need a contiguous intersection among primary_iterators of length >= effort
-            if primary_intervals.length().unwrap_or_default() >= effort {
-                let assigned_intervals = _reduce_intervals(primary_intervals.clone(), effort);
+            // We need an intersection among primary_iterators of length >= effort, respecting
+            // the task's split policy
+            let ramp_up = _ramp_up(project, &res_ids);
+            let selected =
+                _select_intervals(&primary_intervals, effort, task.split_policy, &ramp_up, |gap| {
+                    _is_interruption(project, &res_ids, gap)
+                });
+            if let Some(assigned_intervals) = selected {
                 let hull = assigned_intervals.hull().expect("Cannot be empty");
                 let end_ts = hull.end().value().expect("no unbounded intervals");
                 let assigned_slot = Slot {
                     range: hull,
                     extensible: false,
-                    duration: effort,
+                    duration: assigned_intervals.length().expect("no unbound intervals"),
                     intervals: assigned_intervals,
                 };
                 let mut result_map: HashMap<i32, Slot> = HashMap::new();
@@ -1008,25 +1180,423 @@ fn remove_slot(slots: &mut Vec<Slot>, idx: usize, slot: &Slot) {
     }
 }
 
+/// Take intervals from the start of `pieces` until they cover `work`, a piece of working time
+/// at speed 0.5 covers half its length.
 fn _reduce_intervals(
-    intervals: Intervals<NaiveDateTime>,
-    mut duration: TimeDelta,
+    pieces: &[(Interval<NaiveDateTime>, f64)],
+    mut work: TimeDelta,
 ) -> Intervals<NaiveDateTime> {
     let mut result = Intervals::<NaiveDateTime>::new();
-    for iv in intervals {
-        let iv_length = iv.length().expect("no unbounded intervals");
-        if iv_length < duration {
-            result.insert(iv);
-            duration -= iv_length;
+    for (iv, speed) in pieces {
+        let iv_work = _work(iv, *speed);
+        let iv_start = iv.start().value().expect("no unbound intervals");
+        if iv_work < work {
+            result.insert(*iv);
+            work -= iv_work;
         } else {
-            let iv_start = iv.start().value().expect("no unbound intervals");
-            result.insert(Interval::new_lcro(iv_start, iv_start + duration));
-            duration -= duration;
+            let needed = (work.num_seconds() as f64 / speed).ceil() as i64;
+            let length = TimeDelta::seconds(needed).min(iv.length().expect("no unbound intervals"));
+            result.insert(Interval::new_lcro(iv_start, iv_start + length));
+            work = TimeDelta::zero();
             break;
         }
     }
-    if !duration.is_zero() {
+    if !work.is_zero() {
         panic!("Intervals not long enough to reduce!")
     }
     result
 }
+
+/// Work done during `iv` at the given speed
+fn _work(iv: &Interval<NaiveDateTime>, speed: f64) -> TimeDelta {
+    let length = iv.length().expect("no unbound intervals");
+    TimeDelta::seconds((length.num_seconds() as f64 * speed).floor() as i64)
+}
+
+/// Select intervals covering `work` (working time of experienced resources), ending as early as
+/// possible while respecting the task's split policy. While onboarding (`ramp_up`, see
+/// `_ramp_up`) the same work takes longer. `is_interruption` decides whether the gap between
+/// two consecutive intervals interrupts the task (e.g. nights and weekends do not).
+fn _select_intervals(
+    intervals: &Intervals<NaiveDateTime>,
+    work: TimeDelta,
+    split_policy: SplitPolicy,
+    ramp_up: &[(NaiveDateTime, f64)],
+    is_interruption: impl Fn(&Interval<NaiveDateTime>) -> bool,
+) -> Option<Intervals<NaiveDateTime>> {
+    // split at the ends of ramp-up stages, the speed is constant within each piece
+    let mut pieces: Vec<(Interval<NaiveDateTime>, f64)> = vec![];
+    for iv in intervals {
+        let mut start = iv.start().value().expect("no unbound intervals");
+        let end = iv.end().value().expect("no unbound intervals");
+        for (stage_end, speed) in ramp_up {
+            if start >= *stage_end {
+                continue;
+            }
+            if end <= *stage_end {
+                break;
+            }
+            pieces.push((Interval::new_lcro(start, *stage_end), *speed));
+            start = *stage_end;
+        }
+        let speed =
+            ramp_up.iter().find(|(stage_end, _)| start < *stage_end).map_or(1.0, |(_, s)| *s);
+        if iv.start().value() == Some(start) {
+            pieces.push((*iv, speed));
+        } else {
+            pieces.push((Interval::new_lcro(start, end), speed));
+        }
+    }
+
+    let max_interruptions = match split_policy {
+        SplitPolicy::Free => {
+            let total = pieces.iter().map(|(iv, speed)| _work(iv, *speed)).sum::<TimeDelta>();
+            if total < work {
+                return None;
+            }
+            return Some(_reduce_intervals(&pieces, work));
+        }
+        SplitPolicy::Contiguous => 0,
+        SplitPolicy::MaxInterruptions(n) => n,
+    };
+    // interrupted[idx]: whether the gap before pieces[idx] counts as an interruption, pieces
+    // split at a stage end have no gap
+    let interrupted: Vec<bool> = (0..pieces.len())
+        .map(|idx| {
+            idx > 0 && {
+                let gap_start = pieces[idx - 1].0.end().value().expect("no unbound intervals");
+                let gap_end = pieces[idx].0.start().value().expect("no unbound intervals");
+                gap_start < gap_end && is_interruption(&Interval::new_lcro(gap_start, gap_end))
+            }
+        })
+        .collect();
+
+    // sliding window pieces[first..=idx] with at most `max_interruptions` interruptions
+    let mut first = 0;
+    let mut window_work = TimeDelta::zero();
+    let mut interruptions = 0;
+    for idx in 0..pieces.len() {
+        window_work += _work(&pieces[idx].0, pieces[idx].1);
+        if interrupted[idx] {
+            interruptions += 1;
+        }
+        while interruptions > max_interruptions {
+            window_work -= _work(&pieces[first].0, pieces[first].1);
+            first += 1;
+            if interrupted[first] {
+                interruptions -= 1;
+            }
+        }
+        if window_work >= work {
+            return Some(_reduce_intervals(&pieces[first..=idx], work));
+        }
+    }
+    None
+}
+
+/// Combined onboarding profile of resources working together on a task: (stage end, speed)
+/// sorted by stage end, in the format of `Resource::ramp_up`. Within each stage the average
+/// speed of the resources applies, experienced resources count with 1.0.
+fn _ramp_up(project: &Project, resource_ids: &[i32]) -> Vec<(NaiveDateTime, f64)> {
+    let resources = project
+        .objs
+        .resources
+        .iter()
+        .filter(|r| resource_ids.contains(&r.borrow().db_id))
+        .collect::<Vec<_>>();
+    let stage_ends = resources
+        .iter()
+        .flat_map(|r| r.borrow().ramp_up.iter().map(|(end, _)| *end).collect::<Vec<_>>())
+        .sorted()
+        .dedup()
+        .collect::<Vec<_>>();
+    let mut result = vec![];
+    let mut stage_start: Option<NaiveDateTime> = None;
+    for stage_end in stage_ends {
+        // speeds are constant until the next stage end, use the speed just before it
+        let time = stage_start.unwrap_or(stage_end - TimeDelta::seconds(1));
+        let speed = resources.iter().map(|r| r.borrow().ramp_up_speed(time)).sum::<f64>()
+            / resources.len() as f64;
+        result.push((stage_end, speed));
+        stage_start = Some(stage_end);
+    }
+    result
+}
+
+/// A gap interrupts a task if any of the involved resources would regularly be working during it
+/// (e.g. holidays, vacations or other tasks).
+fn _is_interruption(
+    project: &Project,
+    resource_ids: &[i32],
+    gap: &Interval<NaiveDateTime>,
+) -> bool {
+    project.objs.resources.iter().any(|r| {
+        let r = r.borrow();
+        resource_ids.contains(&r.db_id) && r.regular_availability.overlaps(gap)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduling::datastructures::{ProjectObjects, Resource};
+    use chrono::NaiveDate;
+
+    fn ndt(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .unwrap()
+            .and_hms_opt(
+                time[0..2].parse().unwrap(),
+                time[3..5].parse().unwrap(),
+                time[6..8].parse().unwrap(),
+            )
+            .unwrap()
+    }
+
+    /// Working time 09:00 - 17:00 on the given days of January 2023
+    fn working_days(days: &[u32]) -> Intervals<NaiveDateTime> {
+        let mut intervals = Intervals::new();
+        for day in days {
+            let date = format!("2023-01-{:02}", day);
+            intervals.insert(Interval::new_lcro(ndt(&date, "09:00:00"), ndt(&date, "17:00:00")));
+        }
+        intervals
+    }
+
+    fn lcro(date: &str, start: &str, end: &str) -> Interval<NaiveDateTime> {
+        Interval::new_lcro(ndt(date, start), ndt(date, end))
+    }
+
+    fn resource(db_id: i32, regular_availability: Intervals<NaiveDateTime>) -> Resource {
+        Resource {
+            db_id,
+            name: format!("Resource {}", db_id),
+            timezone: "UTC".to_string(),
+            slots: vec![],
+            regular_availability,
+            last_booking_end: None,
+            ramp_up: vec![],
+        }
+    }
+
+    fn project(resources: Vec<Resource>) -> Project {
+        Project {
+            start: ndt("2023-01-01", "00:00:00"),
+            calculation_end: ndt("2023-02-01", "00:00:00"),
+            objs: ProjectObjects {
+                resources: resources.into_iter().map(|r| Rc::new(RefCell::new(r))).collect(),
+                ..Default::default()
+            },
+            g: Graph::new(),
+            issues: vec![],
+        }
+    }
+
+    #[test]
+    fn test_progress_remaining() {
+        let progress = |iteration| GAProgress {
+            iteration,
+            iterations: 100,
+            best_cost: 0.0,
+            elapsed: Duration::from_secs(10),
+        };
+        assert_eq!(progress(0).remaining(), None);
+        assert_eq!(progress(25).remaining(), Some(Duration::from_secs(30)));
+        assert_eq!(progress(100).remaining(), Some(Duration::ZERO));
+        // more iterations than planned, e.g. after the settings changed
+        assert_eq!(progress(120).remaining(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_is_interruption() {
+        // resource 1 works Monday to Friday, resource 2 also on Saturday
+        let project = project(vec![
+            resource(1, working_days(&[2, 3, 4, 5, 6])),
+            resource(2, working_days(&[2, 3, 4, 5, 6, 7])),
+        ]);
+        let night = lcro("2023-01-02", "17:00:00", "23:59:59");
+        let weekend =
+            Interval::new_lcro(ndt("2023-01-06", "17:00:00"), ndt("2023-01-09", "09:00:00"));
+        let day_off =
+            Interval::new_lcro(ndt("2023-01-03", "17:00:00"), ndt("2023-01-05", "09:00:00"));
+        assert!(!_is_interruption(&project, &[1, 2], &night));
+        assert!(!_is_interruption(&project, &[1], &weekend));
+        assert!(_is_interruption(&project, &[1, 2], &weekend));
+        assert!(_is_interruption(&project, &[1], &day_off));
+        assert!(!_is_interruption(&project, &[3], &day_off));
+    }
+
+    #[test]
+    fn test_select_intervals_free() {
+        let intervals = working_days(&[2, 3, 5]);
+        let selected =
+            _select_intervals(&intervals, TimeDelta::hours(20), SplitPolicy::Free, &[], |_| true)
+                .unwrap();
+        let mut expected = working_days(&[2, 3]);
+        expected.insert(lcro("2023-01-05", "09:00:00", "13:00:00"));
+        assert_eq!(selected, expected);
+        let selected =
+            _select_intervals(&intervals, TimeDelta::hours(25), SplitPolicy::Free, &[], |_| true);
+        assert_eq!(selected, None);
+    }
+
+    #[test]
+    fn test_select_intervals_contiguous() {
+        // nights do not interrupt the task, the gap of the 4th does
+        let intervals = working_days(&[2, 3, 5, 6, 7]);
+        let is_interruption = |gap: &Interval<NaiveDateTime>| {
+            gap.length().expect("no unbound intervals") > TimeDelta::days(1)
+        };
+        let selected = _select_intervals(
+            &intervals,
+            TimeDelta::hours(20),
+            SplitPolicy::Contiguous,
+            &[],
+            is_interruption,
+        )
+        .unwrap();
+        let mut expected = working_days(&[5, 6]);
+        expected.insert(lcro("2023-01-07", "09:00:00", "13:00:00"));
+        assert_eq!(selected, expected);
+
+        // fits before the interruption
+        let selected = _select_intervals(
+            &intervals,
+            TimeDelta::hours(12),
+            SplitPolicy::Contiguous,
+            &[],
+            is_interruption,
+        )
+        .unwrap();
+        let mut expected = working_days(&[2]);
+        expected.insert(lcro("2023-01-03", "09:00:00", "13:00:00"));
+        assert_eq!(selected, expected);
+    }
+
+    #[test]
+    fn test_select_intervals_max_interruptions() {
+        // every gap interrupts the task
+        let intervals = working_days(&[2, 4, 6, 8]);
+        let selected = _select_intervals(
+            &intervals,
+            TimeDelta::hours(20),
+            SplitPolicy::MaxInterruptions(2),
+            &[],
+            |_| true,
+        )
+        .unwrap();
+        let mut expected = working_days(&[2, 4]);
+        expected.insert(lcro("2023-01-06", "09:00:00", "13:00:00"));
+        assert_eq!(selected, expected);
+
+        // the window moves on once it contains too many interruptions
+        let selected = _select_intervals(
+            &intervals,
+            TimeDelta::hours(10),
+            SplitPolicy::MaxInterruptions(1),
+            &[],
+            |_| true,
+        )
+        .unwrap();
+        let mut expected = working_days(&[2]);
+        expected.insert(lcro("2023-01-04", "09:00:00", "11:00:00"));
+        assert_eq!(selected, expected);
+        let selected = _select_intervals(
+            &intervals,
+            TimeDelta::hours(17),
+            SplitPolicy::MaxInterruptions(1),
+            &[],
+            |_| true,
+        );
+        assert_eq!(selected, None);
+    }
+
+    #[test]
+    fn test_select_intervals_no_fit() {
+        // longer than a single day, every gap interrupts
+        let intervals = working_days(&[2, 3]);
+        let selected = _select_intervals(
+            &intervals,
+            TimeDelta::hours(9),
+            SplitPolicy::Contiguous,
+            &[],
+            |_| true,
+        );
+        assert_eq!(selected, None);
+        let selected = _select_intervals(
+            &intervals,
+            TimeDelta::hours(17),
+            SplitPolicy::MaxInterruptions(1),
+            &[],
+            |_| true,
+        );
+        assert_eq!(selected, None);
+        let selected = _select_intervals(
+            &Intervals::new(),
+            TimeDelta::hours(1),
+            SplitPolicy::Free,
+            &[],
+            |_| false,
+        );
+        assert_eq!(selected, None);
+    }
+
+    #[test]
+    fn test_slot_iterator_window() {
+        let intervals = working_days(&[2, 3, 4]);
+        let slots = vec![Slot {
+            range: intervals.hull().unwrap(),
+            extensible: false,
+            duration: intervals.length().unwrap(),
+            intervals: intervals.clone(),
+        }];
+        let start = ndt("2023-01-01", "00:00:00");
+        let unrestricted = _SlotIterator::new(1, &slots, None, start);
+        assert_eq!(unrestricted.current_intervals(), Some(intervals));
+
+        // a team member joining on the 3rd at noon and leaving on the 4th
+        let window: Intervals<NaiveDateTime> =
+            Interval::new_lcro(ndt("2023-01-03", "12:00:00"), ndt("2023-01-04", "00:00:00")).into();
+        let member = _SlotIterator::new(1, &slots, Some(&window), start);
+        let expected: Intervals<NaiveDateTime> = lcro("2023-01-03", "12:00:00", "17:00:00").into();
+        assert_eq!(member.current_intervals(), Some(expected));
+    }
+
+    #[test]
+    fn test_select_intervals_ramp_up() {
+        let intervals = working_days(&[2, 3]);
+        // half speed on the first day: 8h of working time cover 4h of work
+        let ramp_up = [(ndt("2023-01-03", "00:00:00"), 0.5)];
+        let selected =
+            _select_intervals(&intervals, TimeDelta::hours(6), SplitPolicy::Free, &ramp_up, |_| {
+                false
+            })
+            .unwrap();
+        let mut expected = Intervals::new();
+        expected.insert(lcro("2023-01-02", "09:00:00", "17:00:00"));
+        expected.insert(lcro("2023-01-03", "09:00:00", "11:00:00"));
+        assert_eq!(selected, expected);
+
+        // a stage ending within an interval: 4h at half speed, then full speed
+        let ramp_up = [(ndt("2023-01-02", "13:00:00"), 0.5)];
+        let selected = _select_intervals(
+            &intervals,
+            TimeDelta::hours(4),
+            SplitPolicy::Contiguous,
+            &ramp_up,
+            |_| true,
+        )
+        .unwrap();
+        let mut expected = Intervals::new();
+        expected.insert(lcro("2023-01-02", "09:00:00", "15:00:00"));
+        assert_eq!(selected, expected);
+
+        // not enough time at half speed
+        let ramp_up = [(ndt("2023-01-04", "00:00:00"), 0.5)];
+        let selected =
+            _select_intervals(&intervals, TimeDelta::hours(9), SplitPolicy::Free, &ramp_up, |_| {
+                false
+            });
+        assert_eq!(selected, None);
+    }
+}
@@ -663,6 +663,16 @@ impl<T: IntervalValue> Intervals<T> {
         }
         (Intervals { intervals: lhs }, Intervals { intervals: rhs })
     }
+
+    /// Check if the given interval has any timepoints in common with these intervals.
+    /// Binary search, since intervals are sorted and non-overlapping.
+    pub fn overlaps(&self, interval: &Interval<T>) -> bool {
+        let idx = self.intervals.partition_point(|iv| iv.end < interval.start);
+        self.intervals[idx..]
+            .iter()
+            .take_while(|iv| iv.start < interval.end)
+            .any(|iv| !iv.is_disjoint(interval))
+    }
 }
 
 impl<T: IntervalValue> FromIterator<Interval<T>> for Intervals<T> {
@@ -1071,4 +1081,30 @@ mod tests {
         ));
         assert!(intervals.intervals.is_empty());
     }
+
+    #[test]
+    fn test_intervals_overlaps() {
+        let mut intervals = Intervals::new();
+        intervals.insert(Interval::new_lcro(
+            ndt("2023-01-01", "00:00:00"),
+            ndt("2023-01-01", "04:00:00"),
+        ));
+        intervals.insert(Interval::new_lcro(
+            ndt("2023-01-01", "06:00:00"),
+            ndt("2023-01-01", "10:00:00"),
+        ));
+        let check = |start: &str, end: &str| {
+            intervals
+                .overlaps(&Interval::new_lcro(ndt("2023-01-01", start), ndt("2023-01-01", end)))
+        };
+        assert!(check("03:00:00", "05:00:00"));
+        assert!(check("04:00:00", "07:00:00"));
+        assert!(check("01:00:00", "02:00:00"));
+        assert!(!check("04:00:00", "06:00:00"));
+        assert!(!check("10:00:00", "12:00:00"));
+        assert!(!Intervals::new().overlaps(&Interval::new_lcro(
+            ndt("2023-01-01", "00:00:00"),
+            ndt("2023-01-01", "01:00:00")
+        )));
+    }
 }
@@ -4,19 +4,24 @@ mod ga;
 mod interval;
 mod weak_hash_set;
 
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{collections::HashMap, ops::ControlFlow, sync::Arc, time::Duration};
 use tokio::sync::{broadcast::error::RecvError, mpsc::UnboundedReceiver};
+use tracing::{debug, error, info, warn};
 
 pub use datastructures::*;
 pub use db_layer::query_problem;
+pub use ga::GAProgress;
 pub use interval::{Bound, EndBound, Interval, Intervals, StartBound};
 pub use weak_hash_set::WeakHashSet;
 
 use crate::{
-    gql::context::Context,
+    gql::{
+        calculation_run::{CalculationResult, CalculationTrigger},
+        context::Context,
+    },
     scheduling::{
-        db_layer::store_plan,
-        ga::{GASettings, milestone_cost, plan_individual, run_ga},
+        db_layer::{CalculationRun, store_plan, store_run},
+        ga::{GASettings, find_contentions, milestone_cost, plan_individual, run_ga},
     },
 };
 
@@ -40,7 +45,7 @@ pub async fn recalculate_loop(
             maybe_manual = manual_rx.recv() => {
                 if maybe_manual.is_some() {
                     debounce.as_mut().reset(tokio::time::Instant::now() + Duration::from_secs(24*3600*7));
-                    if let Err(e) = perform_recalculation(&app_state).await {
+                    if let Err(e) = perform_recalculation(&app_state, CalculationTrigger::Manual).await {
                         println!("Error recalculating (manual): {}", e);
                     }
                 } else {
@@ -68,7 +73,7 @@ pub async fn recalculate_loop(
                 // timer fired -> start calculation
                 println!("debounce fired");
                 debounce.as_mut().reset(tokio::time::Instant::now() + Duration::from_secs(24*3600*7));
-                if let Err(e) = perform_recalculation(&app_state).await {
+                if let Err(e) = perform_recalculation(&app_state, CalculationTrigger::Debounce).await {
                     println!("Error recalculating (debounce): {}", e);
                 }
             }
@@ -76,51 +81,104 @@ pub async fn recalculate_loop(
     }
 }
 
-async fn perform_recalculation(app_state: &Arc<crate::app_state::AppState>) -> anyhow::Result<()> {
+async fn perform_recalculation(
+    app_state: &Arc<crate::app_state::AppState>,
+    trigger: CalculationTrigger,
+) -> anyhow::Result<()> {
     // build a Context for this calculation
-    app_state.set_state(crate::app_state::CalculationState::Calculating);
-    let ctx = Context::new(Arc::clone(app_state));
+    app_state.set_state(crate::app_state::CalculationState::Calculating(None));
+    app_state.cancel_tx.send_replace(false);
+    // modifications during the calculation make its result outdated
+    let modify_rx = app_state.modify_tx.subscribe();
+    let query_ctx = Context::new(Arc::clone(app_state));
     let settings = GASettings::default();
-    match query_problem(&ctx).await {
+    let started = chrono::Utc::now();
+    let problem = query_problem(&query_ctx).await;
+    // end the read transaction, it would block all writes (including cancelling) during the
+    // calculation. The plan is stored in a new transaction.
+    Arc::into_inner(query_ctx)
+        .expect("This function is the only one with a strong reference.")
+        .rollback()
+        .await?;
+    let ctx = Context::new(Arc::clone(app_state));
+    let mut run = CalculationRun {
+        trigger,
+        result: CalculationResult::Failed,
+        started_at: started,
+        seed: rand::random(),
+        milestone_costs: vec![],
+        issue_count: None,
+    };
+    let res = match problem {
         Err(err) => {
-            println!("Error querying problem: {}", err);
-            return Err(err);
+            warn!("Error querying problem: {}", err);
+            Err(err)
         }
         Ok(mut problem) => {
-            let individual = run_ga(&mut problem, &settings);
-            let task_order =
-                individual.tasks.iter().map(|t| t.task.borrow().title.clone()).collect::<Vec<_>>();
-            println!("Problem recalculated successfully. Task order: {:?}", &task_order);
-            let plan = plan_individual(&problem, &individual);
-            let tasks = problem
-                .objs
-                .tasks
-                .iter()
-                .map(|t| (t.borrow().db_id, t))
-                .collect::<HashMap<i32, _>>();
-            println!("Plan:");
-            for (tid, assignments) in &plan.assignments {
-                let resources: Vec<i32> = assignments.keys().cloned().collect();
-                let task = tasks[&tid].borrow();
-                println!(" {} ({}): {:?}", task.title, tid, resources);
-                println!("    {}", assignments.values().last().unwrap().range);
-            }
-            println!(" -> Costs:");
-            for ms in &problem.objs.milestones {
-                let m = ms.borrow();
-                let cost = milestone_cost(&problem, &settings, &plan, &m);
-                println!(" {} ({}): {}", m.title, m.db_id, cost);
-            }
-            match store_plan(&ctx, &problem, &plan).await {
-                Ok(_) => {
-                    println!("Stored new plan successfully.");
+            let on_progress = |progress: &GAProgress| {
+                if *app_state.cancel_tx.borrow() || !modify_rx.is_empty() {
+                    return ControlFlow::Break(());
+                }
+                app_state.set_state(crate::app_state::CalculationState::Calculating(Some(
+                    progress.clone(),
+                )));
+                ControlFlow::Continue(())
+            };
+            match run_ga(&mut problem, &settings, run.seed, on_progress) {
+                None => {
+                    info!("Recalculation cancelled.");
+                    run.result = CalculationResult::Cancelled;
                 }
-                Err(err) => {
-                    println!("Error storing plan: {}", err);
+                Some(individual) => {
+                    let task_order = individual
+                        .tasks
+                        .iter()
+                        .map(|t| t.task.borrow().title.clone())
+                        .collect::<Vec<_>>();
+                    debug!("Problem recalculated successfully. Task order: {:?}", &task_order);
+                    let mut plan = plan_individual(&problem, &individual);
+                    plan.contentions = find_contentions(&problem, &individual, &plan);
+                    let tasks = problem
+                        .objs
+                        .tasks
+                        .iter()
+                        .map(|t| (t.borrow().db_id, t))
+                        .collect::<HashMap<i32, _>>();
+                    for (tid, assignments) in &plan.assignments {
+                        let resources: Vec<i32> = assignments.keys().cloned().collect();
+                        let task = tasks[&tid].borrow();
+                        debug!(
+                            "Planned {} ({}): {:?} {}",
+                            task.title,
+                            tid,
+                            resources,
+                            assignments.values().last().unwrap().range
+                        );
+                    }
+                    for ms in &problem.objs.milestones {
+                        let m = ms.borrow();
+                        let cost = milestone_cost(&problem, &settings, &plan, &m);
+                        debug!("Cost of milestone {} ({}): {}", m.title, m.db_id, cost);
+                        run.milestone_costs.push((m.db_id, m.title.clone(), cost));
+                    }
+                    run.issue_count = Some(problem.issues.len() + plan.issues.len());
+                    match store_plan(&ctx, &problem, &plan, started).await {
+                        Ok(_) => {
+                            info!("Stored new plan successfully.");
+                            run.result = CalculationResult::Finished;
+                        }
+                        Err(err) => {
+                            error!("Error storing plan: {}", err);
+                        }
+                    }
                 }
             }
             drop(problem);
+            Ok(())
         }
+    };
+    if let Err(err) = store_run(&ctx, &run, &settings).await {
+        error!("Error storing calculation run: {}", err);
     }
 
     match Arc::into_inner(ctx)
@@ -128,9 +186,13 @@ async fn perform_recalculation(app_state: &Arc<crate::app_state::AppState>) -> a
         .commit()
         .await
     {
-        Err(err) => println!("Error committing: {}", err),
+        Err(err) => error!("Error committing: {}", err),
         Ok(_) => {}
     }
-    app_state.set_state(crate::app_state::CalculationState::Finished);
-    Ok(())
+    // the previous plan is kept if the run did not finish
+    app_state.set_state(match run.result {
+        CalculationResult::Finished => crate::app_state::CalculationState::Finished,
+        _ => crate::app_state::CalculationState::Modified,
+    });
+    res
 }